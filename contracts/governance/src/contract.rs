@@ -1,34 +1,45 @@
 use crate::coin_helpers::assert_sent_sufficient_coin_deposit;
 use crate::error::ContractError;
+use crate::locking::LockingContract;
 use crate::msg::{
-    AppProposalResponse, ExecuteMsg, ExtendedPair, InstantiateMsg, MigrateMsg,
-    ProposalResponseTotal, Propose, QueryMsg, SudoMsg,
+    AllowedContractsResponse, AppProposalResponse, DelegatedPowerResponse, DelegationResponse,
+    DepositInfo, DepositListResponse, DepositResponse, ExecuteMsg, ExtendedPair, InstantiateMsg,
+    MigrateMsg, ProposalAction, ProposalResponseTotal, Propose, QueryMsg, SudoMsg,
+    TreasuryResponse, VoteAuditResponse,
 };
 use crate::state::{
-    next_id, AppGovConfig, AppGovConfigResponse, Ballot, Config, Proposal, TokenSupply, Votes,
-    APPGOVCONFIG, APPPROPOSALS, BALLOTS, CONFIG, PROPOSALS, PROPOSALSBYAPP, VOTERDEPOSIT,
+    escrow_deposit, fund_treasury, next_id, proposals, release_deposit, spend_treasury,
+    AppGovConfig, AppGovConfigResponse, AppGovParams, Ballot, Config, DelegatedVote, Proposal,
+    SlashDestination, TokenSupply, Votes, ALLOWEDCONTRACTS, APPGOVCONFIG, APPGOVPARAMS,
+    APPPROPOSALS, APPTREASURY, BALLOTS, CONFIG, DEFAULT_VETO_THRESHOLD, DELEGATEDVOTES,
+    DELEGATIONS, DELEGATORS, ESCROWEDDEPOSITS, PROPOSALSBYAPP, REFUNDCURSOR, SLASHCLAIMS,
+    VOTERBALLOTS, VOTERDEPOSIT,
 };
 use crate::validation::{
-    add_extended_pair_vault, auction_mapping_for_app, collector_lookup_table, get_token_supply,
-    query_app_exists, query_get_asset_data, remove_whitelist_app_id_liquidation,
+    add_auction_params, add_extended_pair_vault, auction_mapping_for_app, burn_gov_tokens_for_app,
+    burnable_balance, collector_lookup_table, get_token_supply, query_app_exists,
+    query_get_asset_data, remove_whitelist_app_id_liquidation,
     remove_whitelist_app_id_vault_interest, remove_whitelist_asset_locker, set_esm_params,
-    update_locker_lsr, update_pairvault_stability, validate_threshold,
+    update_locker_lsr, update_pairvault_stability, validate_proposal_action, validate_proposal_msg,
+    validate_quorum_threshold, validate_treasury_spends, validate_vote_options,
     whitelist_app_id_liquidation, whitelist_app_id_vault_interest, whitelist_asset_locker_eligible,
     whitelist_asset_locker_rewards,
 };
 use comdex_bindings::{ComdexMessages, ComdexQuery};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::{
-    entry_point, to_binary, BankMsg, Binary, BlockInfo, Coin, Decimal, Deps, DepsMut, Env,
-    MessageInfo, Order, QueryRequest, Response, StdError, StdResult, Uint128, WasmQuery,CosmosMsg
+    entry_point, to_binary, Addr, Api, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Empty, Env, Event, MessageInfo, Order, Response, StdError, StdResult, Storage,
+    Timestamp, Uint128,
 };
 use cw2::set_contract_version;
 use cw3::{
     ProposalListResponse, ProposalResponse, Status, Vote, VoteInfo, VoteListResponse, VoteResponse,
 };
 use cw_storage_plus::Bound;
-use cw_utils::{Duration, Threshold, ThresholdResponse};
+use cw_utils::{Duration, Expiration, ThresholdResponse};
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::ops::Div;
 use std::str::FromStr;
 
@@ -36,6 +47,13 @@ use std::str::FromStr;
 const CONTRACT_NAME: &str = "crates.io:governance";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// upper bound on delegators per delegate and app, every one of them is queried
+// from the locking contract when the delegate votes
+const MAX_DELEGATORS: usize = 100;
+
+// upper bound on the choices of a multiple choice proposal
+const MAX_CHOICES: usize = 10;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut<ComdexQuery>,
@@ -48,57 +66,114 @@ pub fn instantiate(
             val: "Funds not allowed.".to_string(),
         });
     };
-    //Only Quorum Threshold allowed for voting
-    match msg.threshold {
-        Threshold::AbsoluteCount { weight: _ } => {
-            return Err(ContractError::AbsoluteCountNotAccepted {})
-        }
-        Threshold::AbsolutePercentage { percentage: _ } => {
-            return Err(ContractError::AbsolutePercentageNotAccepted {})
-        }
-        Threshold::ThresholdQuorum { threshold, quorum } => {
-            validate_threshold(&threshold, &quorum)?
-        }
-    }
+    let veto_threshold = msg
+        .veto_threshold
+        .unwrap_or_else(|| Decimal::percent(DEFAULT_VETO_THRESHOLD));
+    validate_quorum_threshold(&msg.threshold, &veto_threshold)?;
 
     //// set contract version for migration
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     let cfg = Config {
         threshold: msg.threshold,
+        veto_threshold,
         locking_contract: deps.api.addr_validate(msg.locking_contract.as_str())?,
+        guardian: None,
+        admin_app_id: None,
     };
     CONFIG.save(deps.storage, &cfg)?;
     Ok(Response::default())
 }
 #[entry_point]
 pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> Result<Response, ContractError> {
-    match msg {
+    let (app_id, action) = match msg {
         SudoMsg::UpdateLockingContract { address } => {
-            let mut cfg = CONFIG.load(deps.storage)?;
-
-            cfg.locking_contract = deps.api.addr_validate(address.as_str())?;
-            CONFIG.save(deps.storage, &cfg)?;
-            Ok(Response::new())
+            (0, ProposalAction::UpdateLockingContract { address })
         }
-        SudoMsg::UpdateThreshold { threshold } => {
-            match threshold {
-                Threshold::AbsoluteCount { .. } => {
-                    return Err(ContractError::AbsoluteCountNotAccepted {})
-                }
-                Threshold::AbsolutePercentage { .. } => {
-                    return Err(ContractError::AbsolutePercentageNotAccepted {})
-                }
-                Threshold::ThresholdQuorum { threshold, quorum } => {
-                    validate_threshold(&threshold, &quorum)?
-                }
-            }
-            let mut cfg = CONFIG.load(deps.storage)?;
+        SudoMsg::UpdateThreshold {
+            threshold,
+            veto_threshold,
+        } => (
+            0,
+            ProposalAction::UpdateThreshold {
+                threshold,
+                veto_threshold,
+            },
+        ),
+        SudoMsg::UpdateAppGovParams { app_id, params } => {
+            (app_id, ProposalAction::UpdateAppGovParams { params })
+        }
+        SudoMsg::UpdateGuardian { guardian } => (0, ProposalAction::UpdateGuardian { guardian }),
+        SudoMsg::UpdateAllowedContracts {
+            app_id,
+            add,
+            remove,
+        } => (
+            app_id,
+            ProposalAction::UpdateAllowedContracts { add, remove },
+        ),
+        SudoMsg::UpdateAdminApp { app_id } => (0, ProposalAction::UpdateAdminApp { app_id }),
+    };
+    apply_action(deps.storage, deps.api, app_id, action)?;
+    Ok(Response::new())
+}
 
+/// applies a change to the contract's own config, `app_id` is the app whose
+/// params or allowed contracts are updated
+fn apply_action(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    app_id: u64,
+    action: ProposalAction,
+) -> Result<(), ContractError> {
+    let mut cfg = CONFIG.load(storage)?;
+    validate_proposal_action(api, &action, &cfg)?;
+    match action {
+        ProposalAction::UpdateThreshold {
+            threshold,
+            veto_threshold,
+        } => {
             cfg.threshold = threshold;
-            CONFIG.save(deps.storage, &cfg)?;
-            Ok(Response::new())
+            cfg.veto_threshold = veto_threshold.unwrap_or(cfg.veto_threshold);
+        }
+        ProposalAction::UpdateLockingContract { address } => {
+            cfg.locking_contract = api.addr_validate(address.as_str())?;
+        }
+        ProposalAction::UpdateAppGovParams { params } => {
+            APPGOVPARAMS.save(storage, app_id, &params)?;
+        }
+        ProposalAction::UpdateGuardian { guardian } => {
+            cfg.guardian = guardian
+                .map(|guardian| api.addr_validate(guardian.as_str()))
+                .transpose()?;
         }
+        ProposalAction::UpdateAllowedContracts { add, remove } => {
+            for contract in add {
+                ALLOWEDCONTRACTS.save(storage, (app_id, &contract), &Empty {})?;
+            }
+            for contract in remove {
+                ALLOWEDCONTRACTS.remove(storage, (app_id, &contract));
+            }
+        }
+        ProposalAction::UpdateAdminApp { app_id } => {
+            cfg.admin_app_id = app_id;
+        }
+    }
+    CONFIG.save(storage, &cfg)?;
+    Ok(())
+}
+
+// settings shared by all apps only change through the admin app
+fn check_global_actions(
+    storage: &dyn Storage,
+    app_id: u64,
+    actions: &[ProposalAction],
+) -> Result<(), ContractError> {
+    if actions.iter().any(ProposalAction::is_global)
+        && CONFIG.load(storage)?.admin_app_id != Some(app_id)
+    {
+        return Err(ContractError::GlobalActionNotAllowed {});
     }
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -111,24 +186,59 @@ pub fn execute(
     match msg {
         ExecuteMsg::Propose { propose } => execute_propose(deps, env, info, propose),
         ExecuteMsg::Vote { proposal_id, vote } => execute_vote(deps, env, info, proposal_id, vote),
+        ExecuteMsg::VoteWeighted {
+            proposal_id,
+            options,
+        } => execute_vote_weighted(deps, env, info, proposal_id, options),
+        ExecuteMsg::VoteChoice {
+            proposal_id,
+            choice,
+        } => execute_vote_choice(deps, env, info, proposal_id, choice),
         ExecuteMsg::Execute { proposal_id } => execute_execute(deps, env, info, proposal_id),
         ExecuteMsg::Refund { proposal_id } => execute_refund(deps, env, info, proposal_id),
+        ExecuteMsg::RefundAll { proposal_id, limit } => {
+            execute_refund_all(deps, env, info, proposal_id, limit)
+        }
         ExecuteMsg::Deposit { proposal_id } => execute_deposit(deps, env, info, proposal_id),
         ExecuteMsg::Slash { proposal_id } => execute_slash(deps, env, info, proposal_id),
+        ExecuteMsg::ClaimSlashed { proposal_id } => execute_claim_slashed(deps, info, proposal_id),
+        ExecuteMsg::FundTreasury { app_id } => execute_fund_treasury(deps, info, app_id),
+        ExecuteMsg::Delegate { delegate, app_id } => {
+            execute_delegate(deps, env, info, delegate, app_id)
+        }
+        ExecuteMsg::Undelegate { app_id } => execute_undelegate(deps, env, info, app_id),
+        ExecuteMsg::Cancel { proposal_id } => execute_cancel(deps, env, info, proposal_id),
+        ExecuteMsg::GuardianVeto { proposal_id } => {
+            execute_guardian_veto(deps, env, info, proposal_id)
+        }
+        ExecuteMsg::Finalize { proposal_id } => execute_finalize(deps, env, info, proposal_id),
+        ExecuteMsg::FinalizeExpired { app_id, limit } => {
+            execute_finalize_expired(deps, env, info, app_id, limit)
+        }
+        ExecuteMsg::Close { proposal_id } => execute_close(deps, env, info, proposal_id),
     }
 }
 
 pub fn execute_propose(
-    deps: DepsMut<ComdexQuery>,
+    mut deps: DepsMut<ComdexQuery>,
     env: Env,
     info: MessageInfo,
     propose: Propose,
 ) -> Result<Response<ComdexMessages>, ContractError> {
     //get app data for app_id
     let app_response = query_app_exists(deps.as_ref(), propose.app_id_param)?;
-
-    let voting_time = app_response.gov_time_in_seconds;
-    let min_gov_deposit = app_response.min_gov_deposit;
+    let app_params = APPGOVPARAMS
+        .may_load(deps.storage, propose.app_id_param)?
+        .unwrap_or_default();
+
+    // app params override the values of the app
+    let voting_time = app_params
+        .max_voting_period
+        .unwrap_or(app_response.gov_time_in_seconds);
+    let min_gov_deposit = match app_params.min_deposit {
+        Some(min_deposit) => min_deposit,
+        None => Uint128::from_str(&app_response.min_gov_deposit)?,
+    };
     let max_voting_period = Duration::Time(voting_time);
     let gov_token_id = app_response.gov_token_id;
 
@@ -140,14 +250,8 @@ pub fn execute_propose(
 
     let cfg = CONFIG.load(deps.storage)?;
 
-    let query_msg = QueryMsg::Supply {
-        denom: gov_token_denom.clone(),
-    };
-    let query_response: TokenSupply =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: cfg.locking_contract.to_string(),
-            msg: to_binary(&query_msg).unwrap(),
-        }))?;
+    let locking = LockingContract::new(cfg.locking_contract.clone());
+    let query_response = locking.supply(&deps.querier, gov_token_denom.clone())?;
 
     let total_weight = query_response.vtoken as u64;
     if total_weight == 0 {
@@ -156,15 +260,12 @@ pub fn execute_propose(
 
     let cfg = CONFIG.load(deps.storage)?;
 
-    let query_msg = QueryMsg::TotalVTokens {
-        denom: gov_token_denom.clone(),
-        address: info.sender.clone(),
-        height: Some(env.block.height),
-    };
-    let balance_response: Uint128 = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: cfg.locking_contract.to_string(),
-        msg: to_binary(&query_msg).unwrap(),
-    }))?;
+    let balance_response = locking.voting_power(
+        &deps.querier,
+        &info.sender,
+        &gov_token_denom,
+        Some(env.block.height),
+    )?;
 
     let voting_power = Coin {
         amount: balance_response,
@@ -180,6 +281,11 @@ pub fn execute_propose(
     } else if comp.is_none() {
         return Err(ContractError::WrongExpiration {});
     }
+    // the timelock counts seconds from the end of the voting period
+    let timelocked = app_params.execution_delay > 0 || app_params.execution_grace_period.is_some();
+    if timelocked && !matches!(expires, Expiration::AtTime(_)) {
+        return Err(ContractError::TimelockNeedsTimeExpiration {});
+    }
 
     //Check if no other deposit provided other than gov token deposit
     let funds_len = info.funds.len();
@@ -193,28 +299,215 @@ pub fn execute_propose(
     };
 
     let gov_current_deposit = info.funds[0].amount.u128();
+    // held from here on, so the checks below cannot spend it
+    escrow_deposit(deps.storage, &gov_token_denom, gov_current_deposit)?;
+
+    // spam protection, proposers need power, part of the deposit and few open proposals
+    if let Some(min_power) = app_params.min_proposer_power {
+        if balance_response < min_power {
+            return Err(ContractError::InsufficientProposerPower {});
+        }
+    }
+    if let Some(min_initial_deposit) = app_params.min_initial_deposit {
+        if info.funds[0].amount < min_gov_deposit * min_initial_deposit {
+            return Err(ContractError::InsufficientInitialDeposit {});
+        }
+    }
+    if let Some(max_open_proposals) = app_params.max_open_proposals {
+        let open_proposals =
+            open_proposals_of(deps.storage, &env.block, propose.app_id_param, &info.sender)?;
+        if open_proposals >= max_open_proposals {
+            return Err(ContractError::TooManyOpenProposals {});
+        }
+    }
 
     //Handle execution messages
-    for message in propose.msgs.clone() {
+    validate_comdex_msgs(
+        deps.as_ref(),
+        &env,
+        &propose.msgs,
+        propose.app_id_param,
+        &gov_token_denom,
+    )?;
+    let multiple_choice = !propose.choices.is_empty();
+    if multiple_choice {
+        // the winning choice replaces the top level msgs
+        if !propose.msgs.is_empty() || !(2..=MAX_CHOICES).contains(&propose.choices.len()) {
+            return Err(ContractError::InvalidChoices {});
+        }
+        for choice in propose.choices.iter() {
+            validate_comdex_msgs(
+                deps.as_ref(),
+                &env,
+                &choice.msgs,
+                propose.app_id_param,
+                &gov_token_denom,
+            )?;
+        }
+    }
+    for msg in propose.cosmos_msgs.iter() {
+        validate_proposal_msg(deps.as_ref(), msg, propose.app_id_param)?;
+    }
+    validate_treasury_spends(
+        deps.as_ref(),
+        &env.contract.address,
+        propose.app_id_param,
+        &propose.cosmos_msgs,
+    )?;
+    check_global_actions(deps.storage, propose.app_id_param, &propose.actions)?;
+    for action in propose.actions.iter() {
+        validate_proposal_action(deps.api, action, &cfg)?;
+    }
+
+    //check if coins deposited is sufficient to pass minimum deposit
+    //if minimum deposit is achieved ,propsal status becomes "Open" else it becomes "Pending"
+    let min_deposit = Coin {
+        amount: min_gov_deposit,
+        denom: gov_token_denom.clone(),
+    };
+    let deposit_status = assert_sent_sufficient_coin_deposit(&info.funds, Some(min_deposit))?;
+
+    // the proposer votes yes, unless there are choices to pick from
+    let proposer_weight = if multiple_choice {
+        0
+    } else {
+        voting_power.amount.u128()
+    };
+
+    // initialize a proposal
+    let mut prop = Proposal {
+        title: propose.title,
+        description: propose.description,
+        start_time: env.block.time,
+        start_height: env.block.height,
+        expires,
+        msgs: propose.msgs,
+        duration: max_voting_period,
+        status: deposit_status,
+        votes: Votes::yes(proposer_weight),
+        threshold: app_params.apply_threshold(&cfg.threshold),
+        total_weight: Uint128::from(total_weight).u128(),
+        deposit: info.funds.clone(),
+        proposer: info.sender.to_string(),
+        token_denom: gov_token_denom,
+        min_deposit: min_gov_deposit,
+        current_deposit: gov_current_deposit,
+        app_mapping_id: propose.app_id_param,
+        veto_threshold: app_params.veto_threshold.unwrap_or(cfg.veto_threshold),
+        is_slashed: false,
+        refunded_deposit: 0,
+        slashed_deposit: 0,
+        slashable_deposit: 0,
+        voter_reward: 0,
+        end_time: None,
+        cosmos_msgs: propose.cosmos_msgs,
+        actions: propose.actions,
+        choice_votes: vec![0; propose.choices.len()],
+        choices: propose.choices,
+        params: app_params,
+    };
+    prop.slashable_deposit = prop.slashed_part(info.funds[0].amount);
+
+    //update proposal status
+    prop.update_status(&env.block);
+
+    //get latest proposal id counter
+    let id = next_id(deps.storage)?;
+
+    // the proposer also votes with the power delegated to them
+    let delegated_weight = if multiple_choice {
+        0
+    } else {
+        collect_delegated_power(deps.branch(), &cfg, id, &prop, &info.sender)?
+    };
+    prop.votes.add_vote(Vote::Yes, delegated_weight);
+
+    // update proposals
+    proposals().save(deps.storage, id, &prop)?;
+
+    // add the first yes vote from voter
+    if !multiple_choice {
+        let ballot = Ballot {
+            weight: proposer_weight + delegated_weight,
+            vote: Vote::Yes,
+            delegated_weight,
+            choice: None,
+            options: vec![],
+        };
+        BALLOTS.save(deps.storage, (id, &info.sender), &ballot)?;
+        VOTERBALLOTS.save(
+            deps.storage,
+            (&info.sender, propose.app_id_param, id),
+            &Empty {},
+        )?;
+    }
+    VOTERDEPOSIT.save(deps.storage, (id, &info.sender), &info.funds)?;
+
+    let mut app_gov_info = match APPGOVCONFIG.may_load(deps.storage, propose.app_id_param)? {
+        Some(data) => data,
+        None => AppGovConfig {
+            proposal_count: 0,
+            current_supply: Uint128::from(total_weight).u128(),
+            active_participation_supply: 0,
+        },
+    };
+
+    //// update proposal count
+    app_gov_info.proposal_count += 1;
+    //// update current supply
+    app_gov_info.current_supply = Uint128::from(total_weight).u128();
+
+    APPGOVCONFIG.save(deps.storage, propose.app_id_param, &app_gov_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "propose")
+        .add_attribute("proposer", info.sender)
+        .add_attribute("proposal_id", id.to_string())
+        .add_attribute("status", format!("{:?}", prop.status)))
+}
+
+/// number of Pending or Open proposals of a proposer in an app
+fn open_proposals_of(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    app_id: u64,
+    proposer: &Addr,
+) -> StdResult<u32> {
+    let mut open_proposals = 0;
+    for item in proposals()
+        .idx
+        .app_proposer
+        .prefix((app_id, proposer.to_string()))
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (_, prop) = item?;
+        if [Status::Pending, Status::Open].contains(&prop.current_status(block)) {
+            open_proposals += 1;
+        }
+    }
+    Ok(open_proposals)
+}
+
+/// validate checks for the Comdex messages of a proposal
+fn validate_comdex_msgs(
+    deps: Deps<ComdexQuery>,
+    env: &Env,
+    msgs: &[ComdexMessages],
+    app_id_param: u64,
+    gov_token_denom: &str,
+) -> Result<(), ContractError> {
+    // burns of the msgs add up, the balance is queried on the first one
+    let mut burnable = None;
+    for message in msgs.iter().cloned() {
         match message {
             ComdexMessages::MsgWhiteListAssetLocker { app_id, asset_id } => {
-                whitelist_asset_locker_eligible(
-                    deps.as_ref(),
-                    app_id,
-                    asset_id,
-                    propose.app_id_param,
-                )?
+                whitelist_asset_locker_eligible(deps, app_id, asset_id, app_id_param)?
             }
             ComdexMessages::MsgWhitelistAppIdLockerRewards { app_id, asset_id } => {
-                whitelist_asset_locker_rewards(
-                    deps.as_ref(),
-                    app_id,
-                    asset_id,
-                    propose.app_id_param,
-                )?
+                whitelist_asset_locker_rewards(deps, app_id, asset_id, app_id_param)?
             }
             ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id } => {
-                whitelist_app_id_vault_interest(deps.as_ref(), app_id, propose.app_id_param)?
+                whitelist_app_id_vault_interest(deps, app_id, app_id_param)?
             }
             ComdexMessages::MsgAddExtendedPairsVault {
                 app_id,
@@ -233,8 +526,8 @@ pub fn execute_propose(
                 asset_out_price: _,
                 min_usd_value_left: _,
             } => add_extended_pair_vault(
-                deps.as_ref(),
-                propose.app_id_param,
+                deps,
+                app_id_param,
                 ExtendedPair {
                     app_mapping_id_param: app_id,
                     pair_id_param: pair_id,
@@ -257,11 +550,11 @@ pub fn execute_propose(
                 bid_factor: _,
                 debt_lot_size: _,
             } => collector_lookup_table(
-                deps.as_ref(),
+                deps,
                 app_id,
                 collector_asset_id,
                 secondary_asset_id,
-                propose.app_id_param,
+                app_id_param,
             )?,
 
             ComdexMessages::MsgUpdatePairsVault {
@@ -276,12 +569,7 @@ pub fn execute_propose(
                 debt_floor: _,
                 min_usd_value_left: _,
                 is_vault_active: _,
-            } => update_pairvault_stability(
-                deps.as_ref(),
-                app_id,
-                ext_pair_id,
-                propose.app_id_param,
-            )?,
+            } => update_pairvault_stability(deps, app_id, ext_pair_id, app_id_param)?,
 
             ComdexMessages::MsgSetAuctionMappingForApp {
                 app_id,
@@ -291,7 +579,7 @@ pub fn execute_propose(
                 asset_out_oracle_price: _,
                 asset_out_price: _,
                 is_distributor: _,
-            } => auction_mapping_for_app(deps.as_ref(), app_id, propose.app_id_param)?,
+            } => auction_mapping_for_app(deps, app_id, app_id_param)?,
 
             ComdexMessages::MsgUpdateCollectorLookupTable {
                 app_id,
@@ -302,26 +590,21 @@ pub fn execute_propose(
                 lot_size: _,
                 debt_lot_size: _,
                 bid_factor: _,
-            } => update_locker_lsr(deps.as_ref(), app_id, asset_id, propose.app_id_param)?,
+            } => update_locker_lsr(deps, app_id, asset_id, app_id_param)?,
             ComdexMessages::MsgRemoveWhitelistAssetLocker { app_id, asset_id } => {
-                remove_whitelist_asset_locker(
-                    deps.as_ref(),
-                    app_id,
-                    asset_id,
-                    propose.app_id_param,
-                )?
+                remove_whitelist_asset_locker(deps, app_id, asset_id, app_id_param)?
             }
             ComdexMessages::MsgRemoveWhitelistAppIdVaultInterest { app_id } => {
-                remove_whitelist_app_id_vault_interest(deps.as_ref(), app_id, propose.app_id_param)?
+                remove_whitelist_app_id_vault_interest(deps, app_id, app_id_param)?
             }
             ComdexMessages::MsgWhitelistAppIdLiquidation { app_id } => {
-                whitelist_app_id_liquidation(deps.as_ref(), app_id, propose.app_id_param)?
+                whitelist_app_id_liquidation(deps, app_id, app_id_param)?
             }
             ComdexMessages::MsgRemoveWhitelistAppIdLiquidation { app_id } => {
-                remove_whitelist_app_id_liquidation(deps.as_ref(), app_id, propose.app_id_param)?
+                remove_whitelist_app_id_liquidation(deps, app_id, app_id_param)?
             }
             ComdexMessages::MsgAddAuctionParams {
-                app_id: _,
+                app_id,
                 auction_duration_seconds: _,
                 buffer: _,
                 cusp: _,
@@ -331,115 +614,100 @@ pub fn execute_propose(
                 debt_id: _,
                 dutch_id: _,
                 bid_duration_seconds: _,
-            } => (),
+            } => add_auction_params(deps, app_id, app_id_param)?,
+            ComdexMessages::MsgBurnGovTokensForApp {
+                app_id,
+                amount,
+                from,
+            } => {
+                let available = match burnable {
+                    Some(available) => available,
+                    None => burnable_balance(deps, &env.contract.address, gov_token_denom)?,
+                };
+                burn_gov_tokens_for_app(
+                    app_id,
+                    app_id_param,
+                    &amount,
+                    &from,
+                    &env.contract.address,
+                    gov_token_denom,
+                    available,
+                )?;
+                burnable = Some(available - amount.amount);
+            }
             ComdexMessages::MsgAddESMTriggerParams {
                 app_id,
                 target_value: _,
                 cool_off_period: _,
                 asset_id: _,
                 rates: _,
-            } => set_esm_params(deps.as_ref(), app_id, propose.app_id_param)?,
-            _ => return Err(ContractError::ProposalNotEligible {}),
+            } => set_esm_params(deps, app_id, app_id_param)?,
         }
     }
+    Ok(())
+}
 
-    //check if coins deposited is sufficient to pass minimum deposit
-    //if minimum deposit is achieved ,propsal status becomes "Open" else it becomes "Pending"
-    let min_deposit = Coin {
-        amount: Uint128::from_str(&min_gov_deposit)?,
-        denom: gov_token_denom.clone(),
-    };
-    let deposit_status = assert_sent_sufficient_coin_deposit(&info.funds, Some(min_deposit))?;
-
-    // initialize a proposal
-    let mut prop = Proposal {
-        title: propose.title,
-        description: propose.description,
-        start_time: env.block.time,
-        start_height: env.block.height,
-        expires,
-        msgs: propose.msgs,
-        duration: max_voting_period,
-        status: deposit_status,
-        votes: Votes::yes(voting_power.amount.u128()),
-        threshold: cfg.threshold,
-        total_weight: Uint128::from(total_weight).u128(),
-        deposit: info.funds.clone(),
-        proposer: info.sender.to_string(),
-        token_denom: gov_token_denom,
-        min_deposit: Uint128::from_str(&min_gov_deposit)?,
-        current_deposit: gov_current_deposit,
-        app_mapping_id: propose.app_id_param,
-        is_slashed: false,
-    };
-
-    //update proposal status
-    prop.update_status(&env.block);
-
-    //get proposals by app
-    let mut app_proposals = match APPPROPOSALS.may_load(deps.storage, propose.app_id_param)? {
-        Some(record) => record,
-        None => vec![],
-    };
-
-    //get latest proposal id counter
-    let id = next_id(deps.storage)?;
-
-    // update proposals
-    PROPOSALS.save(deps.storage, id, &prop)?;
-    app_proposals.push(crate::state::AppProposalConfig {
-        proposal_id: id,
-        proposal: prop.clone(),
-    });
-    APPPROPOSALS.save(deps.storage, propose.app_id_param, &app_proposals)?;
-
-    // add the first yes vote from voter
-    let ballot = Ballot {
-        weight: voting_power.amount.u128(),
-        vote: Vote::Yes,
-    };
-
-    BALLOTS.save(deps.storage, (id, &info.sender), &ballot)?;
-    VOTERDEPOSIT.save(deps.storage, (id, &info.sender), &info.funds)?;
-
-    let propbyapp = match PROPOSALSBYAPP.may_load(deps.storage, propose.app_id_param)? {
-        Some(data) => Some(data),
-        None => Some(vec![]),
-    };
-
-    let mut app_gov_info = match APPGOVCONFIG.may_load(deps.storage, propose.app_id_param)? {
-        Some(data) => data,
-        None => AppGovConfig {
-            proposal_count: 0,
-            current_supply: Uint128::from(total_weight).u128(),
-            active_participation_supply: 0,
-        },
-    };
-
-    //// update proposal count
-    app_gov_info.proposal_count += 1;
-    //// update current supply
-    app_gov_info.current_supply = Uint128::from(total_weight).u128();
-
-    let mut proposals_by_app = propbyapp.unwrap();
-
-    proposals_by_app.push(id);
-    PROPOSALSBYAPP.save(deps.storage, propose.app_id_param, &proposals_by_app)?;
-    APPGOVCONFIG.save(deps.storage, propose.app_id_param, &app_gov_info)?;
+pub fn execute_vote(
+    deps: DepsMut<ComdexQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    vote: Vote,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    cast_vote(deps, env, info, proposal_id, vote, None, vec![])
+}
 
-    Ok(Response::new()
-        .add_attribute("action", "propose")
-        .add_attribute("proposer", info.sender)
-        .add_attribute("proposal_id", id.to_string())
-        .add_attribute("status", format!("{:?}", prop.status)))
+/// splits the voting power across several options
+pub fn execute_vote_weighted(
+    deps: DepsMut<ComdexQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    mut options: Vec<(Vote, Decimal)>,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    validate_vote_options(&options)?;
+    // the largest share is the main vote of the ballot
+    let mut vote = options[0].0;
+    let mut largest = options[0].1;
+    for (option, share) in options.iter() {
+        if *share > largest {
+            vote = *option;
+            largest = *share;
+        }
+    }
+    if options.len() == 1 {
+        options.clear();
+    }
+    cast_vote(deps, env, info, proposal_id, vote, None, options)
 }
 
-pub fn execute_vote(
+/// yes vote for one of the choices of a multiple choice proposal
+pub fn execute_vote_choice(
     deps: DepsMut<ComdexQuery>,
     env: Env,
     info: MessageInfo,
     proposal_id: u64,
+    choice: u32,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    cast_vote(
+        deps,
+        env,
+        info,
+        proposal_id,
+        Vote::Yes,
+        Some(choice),
+        vec![],
+    )
+}
+
+fn cast_vote(
+    mut deps: DepsMut<ComdexQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
     vote: Vote,
+    choice: Option<u32>,
+    options: Vec<(Vote, Decimal)>,
 ) -> Result<Response<ComdexMessages>, ContractError> {
     if !info.funds.is_empty() {
         return Err(ContractError::CustomError {
@@ -447,7 +715,7 @@ pub fn execute_vote(
         });
     }
     // ensure proposal exists and can be voted on
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
     let status = prop.current_status(&env.block);
 
     // only Open voting status is eligible for voting
@@ -455,50 +723,100 @@ pub fn execute_vote(
         return Err(ContractError::NotOpen {});
     }
 
+    // yes votes on multiple choice proposals go to one of the choices
+    match choice {
+        Some(_) if prop.choices.is_empty() => return Err(ContractError::NotMultipleChoice {}),
+        Some(choice) if choice as usize >= prop.choices.len() => {
+            return Err(ContractError::InvalidChoice {})
+        }
+        None if !prop.choices.is_empty()
+            && (vote == Vote::Yes || options.iter().any(|(option, _)| *option == Vote::Yes)) =>
+        {
+            return Err(ContractError::ChoiceRequired {})
+        }
+        _ => {}
+    }
+
     let cfg = CONFIG.load(deps.storage)?;
-    let token_denom = &prop.token_denom;
 
-    let query_msg = QueryMsg::TotalVTokens {
-        denom: token_denom.to_string(),
-        address: info.sender.clone(),
-        height: Some(prop.start_height),
-    };
-    let balance_response: Uint128 = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: cfg.locking_contract.to_string(),
-        msg: to_binary(&query_msg).unwrap(),
-    }))?;
+    let own_power = LockingContract::new(cfg.locking_contract.clone()).voting_power(
+        &deps.querier,
+        &info.sender,
+        &prop.token_denom,
+        Some(prop.start_height),
+    )?;
 
-    let voting_power = Coin {
-        amount: balance_response,
-        denom: token_denom.clone(),
-    };
-    //check previous vote (if any) in order to change previous vote weights
+    // ballots, the own one or the delegate's that counts this voter, cannot change once locked
     let previous_vote = BALLOTS.may_load(deps.storage, (proposal_id, &info.sender))?;
+    let delegated_vote = DELEGATEDVOTES.may_load(deps.storage, (proposal_id, &info.sender))?;
+    if (previous_vote.is_some() || delegated_vote.is_some()) && prop.votes_locked(&env.block) {
+        return Err(ContractError::VoteLocked {});
+    }
+    if previous_vote.is_some() && prop.params.allow_revoting == Some(false) {
+        return Err(ContractError::AlreadyVoted {});
+    }
+
+    // a direct vote overrides the delegate's vote for this proposal
+    if let Some(delegated_vote) = delegated_vote {
+        let mut delegate_ballot =
+            BALLOTS.load(deps.storage, (proposal_id, &delegated_vote.delegate))?;
+        prop.subtract_ballot(&delegate_ballot);
+        delegate_ballot.weight -= delegated_vote.weight;
+        delegate_ballot.delegated_weight -= delegated_vote.weight;
+        prop.add_ballot(&delegate_ballot);
+        BALLOTS.save(
+            deps.storage,
+            (proposal_id, &delegated_vote.delegate),
+            &delegate_ballot,
+        )?;
+        DELEGATEDVOTES.remove(deps.storage, (proposal_id, &info.sender));
+    }
 
-    if let Some(..) = previous_vote {
-        let prev_vote = previous_vote.unwrap();
-        prop.votes
-            .subtract_vote(prev_vote.vote, voting_power.amount.u128())
+    // delegated power is collected on the first vote only
+    let delegated_weight = match previous_vote {
+        Some(ref prev_vote) => prev_vote.delegated_weight,
+        None => collect_delegated_power(deps.branch(), &cfg, proposal_id, &prop, &info.sender)?,
+    };
+    let voting_power = own_power.u128() + delegated_weight;
+
+    // take back what was counted for the previous ballot, not the current power
+    if let Some(prev_vote) = previous_vote {
+        prop.subtract_ballot(&prev_vote)
     }
 
     let ballot_new = Ballot {
-        weight: voting_power.amount.u128(),
+        weight: voting_power,
         vote,
+        delegated_weight,
+        choice,
+        options,
     };
     //update ballot vote
     BALLOTS.save(deps.storage, (proposal_id, &info.sender), &ballot_new)?;
+    VOTERBALLOTS.save(
+        deps.storage,
+        (&info.sender, prop.app_mapping_id, proposal_id),
+        &Empty {},
+    )?;
 
     // update vote tally
-    prop.votes.add_vote(vote, voting_power.amount.u128());
+    prop.add_ballot(&ballot_new);
     prop.update_status(&env.block);
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    proposals().save(deps.storage, proposal_id, &prop)?;
 
-    Ok(Response::new()
+    let mut res = Response::new()
         .add_attribute("action", "vote")
         .add_attribute("voter", info.sender)
         .add_attribute("proposal_id", proposal_id.to_string())
         .add_attribute("status", format!("{:?}", prop.status))
-        .add_attribute("vote", format!("{:?}", vote)))
+        .add_attribute("vote", format!("{:?}", vote));
+    if let Some(choice) = choice {
+        res = res.add_attribute("choice", choice.to_string());
+    }
+    if !ballot_new.options.is_empty() {
+        res = res.add_attribute("options", format!("{:?}", ballot_new.options));
+    }
+    Ok(res)
 }
 
 pub fn execute_execute(
@@ -513,23 +831,72 @@ pub fn execute_execute(
         });
     }
     //Anyone can trigger the execution if the proposal current status is Passed
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
     let status = prop.current_status(&env.block);
 
     if status != Status::Passed {
         return Err(ContractError::WrongExecuteStatus {});
     }
 
-    if prop.msgs.is_empty() {
+    // timelock, stakers get the execution delay to react before changes apply
+    if !prop.eta().is_expired(&env.block) {
+        return Err(ContractError::TimelockNotExpired {});
+    }
+    if let Some(deadline) = prop.execution_deadline() {
+        if deadline.is_expired(&env.block) {
+            return Err(ContractError::ExecutionWindowExpired {});
+        }
+    }
+
+    // multiple choice proposals dispatch the msgs of the winning choice
+    let msgs = match prop.winning_choice() {
+        Some(choice) => prop.choices[choice].msgs.clone(),
+        None => prop.msgs.clone(),
+    };
+    if msgs.is_empty() && prop.cosmos_msgs.is_empty() && prop.actions.is_empty() {
         return Err(ContractError::NoMessage {});
     }
+    // allowed contracts, the treasury and the balance to burn may have changed
+    // since the proposal was created
+    for msg in prop.cosmos_msgs.iter() {
+        validate_proposal_msg(deps.as_ref(), msg, prop.app_mapping_id)?;
+    }
+    let spends = validate_treasury_spends(
+        deps.as_ref(),
+        &env.contract.address,
+        prop.app_mapping_id,
+        &prop.cosmos_msgs,
+    )?;
+    let burns: Vec<ComdexMessages> = msgs
+        .iter()
+        .filter(|msg| matches!(msg, ComdexMessages::MsgBurnGovTokensForApp { .. }))
+        .cloned()
+        .collect();
+    validate_comdex_msgs(
+        deps.as_ref(),
+        &env,
+        &burns,
+        prop.app_mapping_id,
+        &prop.token_denom,
+    )?;
+    // so may the admin app
+    check_global_actions(deps.storage, prop.app_mapping_id, &prop.actions)?;
+
     //Set it to executed
     prop.status = Status::Executed;
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    proposals().save(deps.storage, proposal_id, &prop)?;
+
+    for action in prop.actions {
+        apply_action(deps.storage, deps.api, prop.app_mapping_id, action)?;
+    }
+    for spend in spends.iter() {
+        spend_treasury(deps.storage, prop.app_mapping_id, spend)?;
+    }
 
     //Dispatch all proposed messages
     Ok(Response::new()
-        .add_messages(prop.msgs)
+        .add_messages(msgs)
+        .add_messages(prop.cosmos_msgs)
         .add_attribute("action", "execute")
         .add_attribute("sender", info.sender)
         .add_attribute("proposal_id", proposal_id.to_string()))
@@ -547,7 +914,7 @@ pub fn execute_deposit(
         });
     }
     // Get proposal latest status
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
     let status = prop.current_status(&env.block);
 
     // Validate sent tokens are the currect denom
@@ -557,9 +924,16 @@ pub fn execute_deposit(
 
     // only Open or Pending Proposals are eligible for deposit
 
-    if [Status::Executed, Status::Rejected, Status::Passed]
-        .iter()
-        .any(|x| *x == status)
+    if [
+        Status::Executed,
+        Status::Rejected,
+        Status::Passed,
+        Status::Cancelled,
+        Status::Vetoed,
+        Status::DepositFailed,
+    ]
+    .iter()
+    .any(|x| *x == status)
     {
         return Err(ContractError::CannotDeposit {});
     }
@@ -570,36 +944,30 @@ pub fn execute_deposit(
         Some(record) => record,
         None => vec![],
     };
-    let mut deposit_amount: u128 = 0;
-    if !deposit_info.is_empty() {
-        for current_deposit_coin in deposit_info.iter_mut() {
-            if info.funds[0].denom == current_deposit_coin.denom {
-                current_deposit_coin.amount += info.funds[0].amount;
-                deposit_amount = info.funds[0].amount.u128();
-            }
-        }
-    } else {
-        let mut is_correct_fund = false;
-        if prop.token_denom == info.funds[0].denom {
-            deposit_amount = info.funds[0].amount.u128();
-            is_correct_fund = true;
-        }
-        if is_correct_fund {
-            deposit_info = info.funds.clone();
-        } else {
-            return Err(ContractError::IncorrectDenomDeposit {});
-        }
+    prop.add_deposit(&deposit_info, &info.funds[0]);
+    escrow_deposit(deps.storage, &prop.token_denom, info.funds[0].amount.u128())?;
+    match deposit_info
+        .iter_mut()
+        .find(|coin| coin.denom == info.funds[0].denom)
+    {
+        Some(coin) => coin.amount += info.funds[0].amount,
+        None => deposit_info.push(info.funds[0].clone()),
     }
 
-    prop.deposit.push(info.funds[0].clone());
-    prop.current_deposit += deposit_amount;
-
-    if Uint128::from(prop.current_deposit) > Uint128::from(prop.min_deposit) {
-        prop.status = Status::Open
+    if status == Status::Pending
+        && Uint128::from(prop.current_deposit) > Uint128::from(prop.min_deposit)
+    {
+        prop.status = Status::Open;
+        // the voting period starts once the proposal opens
+        if let Expiration::AtTime(expires) = prop.expires {
+            let voting_period = expires.seconds().saturating_sub(prop.start_time.seconds());
+            prop.expires = Expiration::AtTime(env.block.time.plus_seconds(voting_period));
+        }
+        prop.start_time = env.block.time;
     }
 
     VOTERDEPOSIT.save(deps.storage, (proposal_id, &info.sender), &deposit_info)?;
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    proposals().save(deps.storage, proposal_id, &prop)?;
 
     Ok(Response::new()
         .add_attribute("action", "deposit")
@@ -619,8 +987,95 @@ pub fn execute_refund(
         });
     }
     // Get proposal status
-    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
-    let status = prop.current_status(&env.block);
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
+    check_refundable(&prop, &env.block)?;
+
+    // get sender deposit info
+    let deposit_info = match VOTERDEPOSIT.may_load(deps.storage, (proposal_id, &info.sender))? {
+        Some(deposit_info) => deposit_info,
+        // If no reposit for the proposal
+        None => return Err(ContractError::NoDeposit {}),
+    };
+
+    VOTERDEPOSIT.remove(deps.storage, (proposal_id, &info.sender));
+    let refund = prop.refundable(&env.block, &deposit_info);
+    let refunded = prop.refund_deposit(&refund);
+    release_deposit(deps.storage, &prop.token_denom, refunded)?;
+
+    proposals().save(deps.storage, proposal_id, &prop)?;
+
+    let mut res = Response::new();
+    if !refund.is_empty() {
+        res = res.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: refund,
+        });
+    }
+    Ok(res
+        .add_attribute("action", "refund")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn execute_refund_all(
+    deps: DepsMut<ComdexQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    limit: Option<u32>,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
+    check_refundable(&prop, &env.block)?;
+
+    // resume after the last depositor refunded by a previous call
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let cursor = REFUNDCURSOR.may_load(deps.storage, proposal_id)?;
+    let start = cursor.as_ref().map(Bound::exclusive);
+    let deposits = VOTERDEPOSIT
+        .prefix(proposal_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let done = deposits.len() <= limit;
+
+    let mut refunds = vec![];
+    let mut refunded = 0;
+    for (depositor, amount) in deposits.into_iter().take(limit) {
+        VOTERDEPOSIT.remove(deps.storage, (proposal_id, &depositor));
+        REFUNDCURSOR.save(deps.storage, proposal_id, &depositor)?;
+        let refund = prop.refundable(&env.block, &amount);
+        if refund.is_empty() {
+            continue;
+        }
+        refunded += prop.refund_deposit(&refund);
+        refunds.push(BankMsg::Send {
+            to_address: depositor.to_string(),
+            amount: refund,
+        });
+    }
+    if done {
+        REFUNDCURSOR.remove(deps.storage, proposal_id);
+    }
+    release_deposit(deps.storage, &prop.token_denom, refunded)?;
+    proposals().save(deps.storage, proposal_id, &prop)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "refund_all")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("refunded", refunds.len().to_string())
+        .add_attribute("done", done.to_string())
+        .add_messages(refunds))
+}
+
+// Deposits of closed proposals can be refunded unless they are to be burnt
+fn check_refundable(prop: &Proposal, block: &BlockInfo) -> Result<(), ContractError> {
+    let status = prop.current_status(block);
 
     // Open and Pending proposal status are not eligible for refund
     if status == Status::Pending {
@@ -631,33 +1086,11 @@ pub fn execute_refund(
         return Err(ContractError::OpenProposal {});
     }
 
-    //disallow slashed proposal
-    if status == Status::Rejected && prop.check_vetoed(&env.block) {
+    //disallow slashed proposal, unless part of the deposit stays refundable
+    if prop.is_slashable(block) && prop.slash_fraction() == Decimal::one() {
         return Err(ContractError::SlashedProposal {});
     }
-
-    // get sender deposit info
-    let deposit_info = VOTERDEPOSIT.may_load(deps.storage, (proposal_id, &info.sender))?;
-
-    // If no reposit for the proposal
-    if deposit_info.is_none() {
-        return Err(ContractError::NoDeposit {});
-    }
-
-    //// need to update current_deposit////////
-
-    VOTERDEPOSIT.remove(deps.storage, (proposal_id, &info.sender));
-
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
-
-    Ok(Response::new()
-        .add_message(BankMsg::Send {
-            to_address: info.sender.to_string(),
-            amount: deposit_info.unwrap(),
-        })
-        .add_attribute("action", "refund")
-        .add_attribute("sender", info.sender)
-        .add_attribute("proposal_id", proposal_id.to_string()))
+    Ok(())
 }
 
 pub fn execute_slash(
@@ -671,16 +1104,15 @@ pub fn execute_slash(
             val: "Funds deposit not allowed".to_string(),
         });
     }
-    let mut prop = PROPOSALS.load(deps.storage, proposal_id)?;
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
     let status = prop.current_status(&env.block);
 
-    //Check if proposal is rejected
-    if status != Status::Rejected {
-        return Err(ContractError::NotRejected {});
-    }
-
-    // check if proposal is vetoed
-    if !prop.check_vetoed(&env.block) {
+    // vetoed proposals, and depending on the app failed deposits and quorums
+    if !prop.is_slashable(&env.block) {
+        //Check if proposal is rejected
+        if status != Status::Rejected {
+            return Err(ContractError::NotRejected {});
+        }
         return Err(ContractError::ProposalNotVetoed {});
     }
 
@@ -689,472 +1121,2014 @@ pub fn execute_slash(
         return Err(ContractError::AlreadySlashed {});
     }
 
-    let deposit_amount = prop.current_deposit;
-    let deposit_denom = prop.token_denom.clone();
-    let slash_amount = Coin {
-        amount: Uint128::from(deposit_amount),
-        denom: deposit_denom,
-    };
-    prop.is_slashed = true;
+    // refunded deposits are no longer held by the contract
+    let slash_amount = prop.slash_deposit();
+    let msgs = slash_msgs(&env, &prop, slash_amount);
+    // the voter reward stays held until it is claimed
+    release_deposit(
+        deps.storage,
+        &prop.token_denom,
+        slash_amount - prop.voter_reward,
+    )?;
 
-    PROPOSALS.save(deps.storage, proposal_id, &prop)?;
+    proposals().save(deps.storage, proposal_id, &prop)?;
 
     Ok(Response::new()
-        .add_message(ComdexMessages::MsgBurnGovTokensForApp {
-            app_id: prop.app_mapping_id,
-            amount: slash_amount,
-            from: env.contract.address.to_string(),
-        })
+        .add_messages(msgs)
         .add_attribute("action", "Slash")
         .add_attribute("trigger_address", info.sender)
         .add_attribute("proposal_id", proposal_id.to_string()))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps<ComdexQuery>, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Threshold { proposal_id } => to_binary(&query_threshold(deps, proposal_id)?),
-        QueryMsg::Proposal { proposal_id } => {
-            to_binary(&query_proposal_detailed(deps, env, proposal_id)?)
+pub fn execute_fund_treasury(
+    deps: DepsMut<ComdexQuery>,
+    info: MessageInfo,
+    app_id: u64,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    if info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "No funds sent".to_string(),
+        });
+    }
+    query_app_exists(deps.as_ref(), app_id)?;
+    for coin in info.funds.iter() {
+        fund_treasury(deps.storage, app_id, coin)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "fund_treasury")
+        .add_attribute("sender", info.sender)
+        .add_attribute("app_id", app_id.to_string()))
+}
+
+pub fn execute_claim_slashed(
+    deps: DepsMut<ComdexQuery>,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let prop = proposals().load(deps.storage, proposal_id)?;
+    if prop.voter_reward == 0 {
+        return Err(ContractError::NothingToClaim {});
+    }
+    if SLASHCLAIMS.has(deps.storage, (proposal_id, &info.sender)) {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+
+    // the reward is split by the weight of the No and Veto votes, each voter claims
+    // for its own power, a delegator for its part of the delegate's ballot
+    let (ballot, own_weight) = match BALLOTS.may_load(deps.storage, (proposal_id, &info.sender))? {
+        Some(ballot) => {
+            let own_weight = ballot.weight - ballot.delegated_weight;
+            (Some(ballot), own_weight)
         }
-        QueryMsg::Vote { proposal_id, voter } => to_binary(&query_vote(deps, proposal_id, voter)?),
-        QueryMsg::ListProposals { start_after, limit } => {
-            to_binary(&list_proposals(deps, env, start_after, limit)?)
+        None => match DELEGATEDVOTES.may_load(deps.storage, (proposal_id, &info.sender))? {
+            Some(delegated_vote) => (
+                BALLOTS.may_load(deps.storage, (proposal_id, &delegated_vote.delegate))?,
+                delegated_vote.weight,
+            ),
+            None => (None, 0),
+        },
+    };
+    let weight = match ballot {
+        Some(ballot) if ballot.weight > 0 => {
+            let vote_weight = ballot.vote_weight();
+            Uint128::from(vote_weight.no + vote_weight.veto)
+                .multiply_ratio(own_weight, ballot.weight)
+                .u128()
         }
-        QueryMsg::ReverseProposals {
-            start_before,
-            limit,
-        } => to_binary(&reverse_proposals(deps, env, start_before, limit)?),
-        QueryMsg::ListVotes {
-            proposal_id,
-            start_after,
-            limit,
-        } => to_binary(&list_votes(deps, proposal_id, start_after, limit)?),
-        QueryMsg::ListAppProposal {
-            app_id,
-            start_after,
-            limit,
-            status,
-        } => to_binary(&get_proposals_by_app(
-            deps,
-            env,
-            app_id,
-            start_after,
-            limit,
-            status,
-        )?),
-        QueryMsg::AppAllUpData { app_id } => to_binary(&get_all_up_info_by_app(deps, env, app_id)?),
-        QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::ParticipationStats { app_id } => {
-            to_binary(&get_all_up_info_by_app_ratio(deps, env, app_id)?)
+        _ => 0,
+    };
+    if weight == 0 {
+        return Err(ContractError::NothingToClaim {});
+    }
+    let share =
+        Uint128::from(prop.voter_reward).multiply_ratio(weight, prop.votes.no + prop.votes.veto);
+    SLASHCLAIMS.save(deps.storage, (proposal_id, &info.sender), &share)?;
+    release_deposit(deps.storage, &prop.token_denom, share.u128())?;
+
+    let mut res = Response::new();
+    if !share.is_zero() {
+        res = res.add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![Coin {
+                amount: share,
+                denom: prop.token_denom,
+            }],
+        });
+    }
+    Ok(res
+        .add_attribute("action", "claim_slashed")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("amount", share.to_string()))
+}
+
+// sends the slashed deposit to where the app wants it
+fn slash_msgs(env: &Env, prop: &Proposal, amount: u128) -> Vec<CosmosMsg<ComdexMessages>> {
+    let coin = |amount: u128| Coin {
+        amount: Uint128::from(amount),
+        denom: prop.token_denom.clone(),
+    };
+    if amount == 0 {
+        return vec![];
+    }
+    let mut msgs: Vec<CosmosMsg<ComdexMessages>> = vec![];
+    let mut burn_amount = amount;
+    match &prop.params.slash_destination {
+        SlashDestination::Burn => {}
+        SlashDestination::CommunityPool { address } => {
+            msgs.push(
+                BankMsg::Send {
+                    to_address: address.to_string(),
+                    amount: vec![coin(amount)],
+                }
+                .into(),
+            );
+            burn_amount = 0;
         }
-        _ => panic!("Not implemented"),
+        // claimed by the voters, burnt when nobody voted No or Veto
+        SlashDestination::NoVetoVoters => burn_amount -= prop.voter_reward,
     }
+    if burn_amount > 0 {
+        msgs.push(
+            ComdexMessages::MsgBurnGovTokensForApp {
+                app_id: prop.app_mapping_id,
+                amount: coin(burn_amount),
+                from: env.contract.address.to_string(),
+            }
+            .into(),
+        );
+    }
+    msgs
 }
 
-fn query_threshold(deps: Deps<ComdexQuery>, proposal_id: u64) -> StdResult<ThresholdResponse> {
-    let cfg = CONFIG.load(deps.storage)?;
-    let prop = PROPOSALS.load(deps.storage, proposal_id)?;
+pub fn execute_cancel(
+    deps: DepsMut<ComdexQuery>,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
+    if prop.proposer != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    Ok(cfg.threshold.to_response(prop.total_weight))
-}
+    // Pending proposals, or Open ones where nobody but the proposer has voted
+    let status = prop.current_status(&env.block);
+    let only_proposer_voted = BALLOTS
+        .prefix(proposal_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .all(|voter| matches!(voter, Ok(voter) if voter == info.sender));
+    let cancellable = match status {
+        Status::Pending => true,
+        Status::Open => only_proposer_voted,
+        _ => false,
+    };
+    if !cancellable {
+        return Err(ContractError::CannotCancel {});
+    }
 
-fn query_config(deps: Deps<ComdexQuery>) -> StdResult<Config> {
-    let cfg = CONFIG.load(deps.storage)?;
+    // refund every depositor
+    let deposits = VOTERDEPOSIT
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut refunds = vec![];
+    let mut refunded = 0;
+    for (depositor, amount) in deposits {
+        VOTERDEPOSIT.remove(deps.storage, (proposal_id, &depositor));
+        refunded += prop.refund_deposit(&amount);
+        refunds.push(BankMsg::Send {
+            to_address: depositor.to_string(),
+            amount,
+        });
+    }
+    release_deposit(deps.storage, &prop.token_denom, refunded)?;
 
-    Ok(cfg)
+    prop.status = Status::Cancelled;
+    proposals().save(deps.storage, proposal_id, &prop)?;
+
+    Ok(Response::new()
+        .add_messages(refunds)
+        .add_attribute("action", "cancel")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
 }
 
-fn query_proposal_detailed(
-    deps: Deps<ComdexQuery>,
+pub fn execute_guardian_veto(
+    deps: DepsMut<ComdexQuery>,
     env: Env,
-    id: u64,
-) -> StdResult<ProposalResponseTotal> {
-    let prop = PROPOSALS.load(deps.storage, id)?;
-    let status = prop.current_status(&env.block);
-    Ok(ProposalResponseTotal {
-        id,
-        title: prop.title,
-        description: prop.description,
-        msgs: prop.msgs,
-        status,
-        start_time: prop.start_time,
-        expires: prop.expires,
-        votes: prop.votes,
-        duration: prop.duration,
-        start_height: prop.start_height,
-        threshold: prop.threshold,
-        proposer: prop.proposer,
-        token_denom: prop.token_denom,
-        total_weight: prop.total_weight,
-        current_deposit: prop.current_deposit,
-    })
-}
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let cfg = CONFIG.load(deps.storage)?;
+    if cfg.guardian != Some(info.sender.clone()) {
+        return Err(ContractError::Unauthorized {});
+    }
 
-// settings for pagination
-const MAX_LIMIT: u32 = 300;
-const DEFAULT_LIMIT: u32 = 100;
+    // only passed, not yet executed proposals can be vetoed
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
+    if prop.current_status(&env.block) != Status::Passed {
+        return Err(ContractError::NotPassed {});
+    }
 
-fn list_proposals(
-    deps: Deps<ComdexQuery>,
-    env: Env,
-    start_after: Option<u64>,
-    limit: Option<u32>,
-) -> StdResult<ProposalListResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after.map(Bound::exclusive);
-    let proposals = PROPOSALS
-        .range(deps.storage, start, None, Order::Ascending)
-        .take(limit)
-        .map(|p| map_proposal(&env.block, p))
-        .collect::<StdResult<_>>()?;
+    prop.status = Status::Vetoed;
+    proposals().save(deps.storage, proposal_id, &prop)?;
 
-    Ok(ProposalListResponse { proposals })
+    Ok(Response::new()
+        .add_event(
+            Event::new("proposal_vetoed")
+                .add_attribute("proposal_id", proposal_id.to_string())
+                .add_attribute("app_id", prop.app_mapping_id.to_string())
+                .add_attribute("guardian", info.sender.clone()),
+        )
+        .add_attribute("action", "guardian_veto")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
 }
 
-fn get_proposals_by_app(
-    deps: Deps<ComdexQuery>,
+pub fn execute_finalize(
+    deps: DepsMut<ComdexQuery>,
     env: Env,
-    app_id: u64,
-    start_after: u32,
-    limit: Option<u32>,
-    status: Option<Status>,
-) -> StdResult<AppProposalResponse> {
-    let mut info = match PROPOSALSBYAPP.may_load(deps.storage, app_id)? {
-        Some(record) => record,
-        None => vec![],
-    };
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
+    let event = finalize_proposal(deps.storage, &mut prop, proposal_id, &env.block)?;
 
-    let mut all_proposals = vec![];
-    info.reverse();
-    for i in info {
-        let proposal = query_proposal_detailed(deps, env.clone(), i)?;
-        match status {
-            Some(status) => {
-                if status == proposal.status {
-                    all_proposals.push(proposal.clone());
-                }
-            }
-            None => {
-                all_proposals.push(proposal);
-            }
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "finalize")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+pub fn execute_finalize_expired(
+    deps: DepsMut<ComdexQuery>,
+    env: Env,
+    info: MessageInfo,
+    app_id: u64,
+    limit: Option<u32>,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    // only proposals stored as Pending or Open need finalizing, Finalize is
+    // the only one storing Passed and it sets `end_time` along with it
+    let sources = [Status::Pending, Status::Open]
+        .iter()
+        .map(|status| {
+            proposals()
+                .idx
+                .status
+                .prefix((app_id, *status as u8))
+                .range(deps.storage, None, None, Order::Ascending)
+        })
+        .collect();
+    let candidates = merge_by_id(sources, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // proposals still running are skipped
+    let mut events = vec![];
+    for (proposal_id, mut prop) in candidates {
+        match finalize_proposal(deps.storage, &mut prop, proposal_id, &env.block) {
+            Ok(event) => events.push(event),
+            Err(ContractError::AlreadyFinalized {} | ContractError::VotingNotEnded {}) => {}
+            Err(err) => return Err(err),
         }
     }
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = start_after as usize;
-    let checkpoint = start + limit;
-    let mut proposal = vec![];
-    if !all_proposals.is_empty() {
-        // If the vec len is smaller than start_after, then empty vec
-        // If the checkpoint is >= length, then return all remaining elements
-        // else return the specific elements
-        let length = all_proposals.len();
-        if length <= start {
-            proposal = vec![];
-        } else if checkpoint >= length {
-            proposal = all_proposals[start..].to_vec();
-        } else {
-            proposal = all_proposals[start..checkpoint].to_vec();
-        }
-    }
-    let proposal_length = all_proposals.len();
 
-    Ok(AppProposalResponse {
-        proposals: proposal,
-        proposal_count: proposal_length as u64,
-    })
+    Ok(Response::new()
+        .add_attribute("action", "finalize_expired")
+        .add_attribute("sender", info.sender)
+        .add_attribute("app_id", app_id.to_string())
+        .add_attribute("finalized", events.len().to_string())
+        .add_events(events))
 }
 
-fn get_all_up_info_by_app(
-    deps: Deps<ComdexQuery>,
+pub fn execute_close(
+    deps: DepsMut<ComdexQuery>,
     env: Env,
-    app_id: u64,
-) -> StdResult<AppGovConfig> {
-    let info = match PROPOSALSBYAPP.may_load(deps.storage, app_id)? {
-        Some(record) => record,
-        None => vec![],
-    };
-
-    let app_response = query_app_exists(deps, app_id)?;
-    let gov_token_id = app_response.gov_token_id;
-    let total_weight = get_token_supply(deps, app_id, gov_token_id)?;
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    let mut prop = proposals().load(deps.storage, proposal_id)?;
+    if [
+        Status::Executed,
+        Status::Passed,
+        Status::Cancelled,
+        Status::Vetoed,
+    ]
+    .iter()
+    .any(|x| *x == prop.current_status(&env.block))
+    {
+        return Err(ContractError::WrongCloseStatus {});
+    }
+    // expired proposals that did not pass are Rejected, or DepositFailed if they never opened
+    let event = finalize_proposal(deps.storage, &mut prop, proposal_id, &env.block)?;
 
-    let mut participation_info = APPGOVCONFIG.may_load(deps.storage, app_id)?.unwrap();
-    let mut total_votes_weight: u128 = 0;
+    Ok(Response::new()
+        .add_event(event)
+        .add_attribute("action", "close")
+        .add_attribute("sender", info.sender)
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
 
-    for i in info {
-        let proposal = query_proposal_detailed(deps, env.clone(), i)?;
-        total_votes_weight += proposal.votes.total();
+// stores the status of a proposal once it can no longer change by itself,
+// along with the time it happened
+fn finalize_proposal(
+    storage: &mut dyn Storage,
+    prop: &mut Proposal,
+    proposal_id: u64,
+    block: &BlockInfo,
+) -> Result<Event, ContractError> {
+    if prop.end_time.is_some() {
+        return Err(ContractError::AlreadyFinalized {});
     }
-    participation_info.current_supply = Uint128::from(total_weight).u128();
-    participation_info.active_participation_supply = total_votes_weight;
-    participation_info.proposal_count += 1;
-    Ok(participation_info)
+    let status = prop.current_status(block);
+    let event = match status {
+        Status::Passed => "proposal_passed",
+        Status::Rejected => "proposal_rejected",
+        Status::DepositFailed => "proposal_deposit_failed",
+        Status::Pending | Status::Open => return Err(ContractError::VotingNotEnded {}),
+        // executed, cancelled and vetoed proposals are stored when that happens
+        _ => return Err(ContractError::AlreadyFinalized {}),
+    };
+    prop.status = status;
+    prop.end_time = Some(block.time);
+    proposals().save(storage, proposal_id, prop)?;
+
+    Ok(Event::new(event)
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("app_id", prop.app_mapping_id.to_string())
+        .add_attribute("yes", prop.votes.yes.to_string())
+        .add_attribute("no", prop.votes.no.to_string())
+        .add_attribute("abstain", prop.votes.abstain.to_string())
+        .add_attribute("veto", prop.votes.veto.to_string())
+        .add_attribute("end_time", block.time.seconds().to_string()))
 }
 
-fn get_all_up_info_by_app_ratio(
-    deps: Deps<ComdexQuery>,
-    env: Env,
+pub fn execute_delegate(
+    deps: DepsMut<ComdexQuery>,
+    _env: Env,
+    info: MessageInfo,
+    delegate: String,
     app_id: u64,
-) -> StdResult<AppGovConfigResponse> {
-    let info = match PROPOSALSBYAPP.may_load(deps.storage, app_id)? {
-        Some(record) => record,
-        None => vec![],
-    };
-
-    let app_response = query_app_exists(deps, app_id)?;
-    let gov_token_id = app_response.gov_token_id;
-    let total_weight = get_token_supply(deps, app_id, gov_token_id)?;
+) -> Result<Response<ComdexMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let delegate = deps.api.addr_validate(&delegate)?;
+    if delegate == info.sender {
+        return Err(ContractError::SelfDelegation {});
+    }
 
-    let mut participation_info = APPGOVCONFIG.may_load(deps.storage, app_id)?.unwrap();
-    let mut total_votes_weight: u128 = 0;
-    let mut total_target_weight: u128 = 0;
+    // drop the previous delegation (if any) for the app
+    if let Some(previous) = DELEGATIONS.may_load(deps.storage, (app_id, &info.sender))? {
+        DELEGATORS.remove(deps.storage, (app_id, &previous, &info.sender));
+    }
 
-    for i in info {
-        let proposal = query_proposal_detailed(deps, env.clone(), i)?;
-        total_votes_weight += proposal.votes.total();
-        total_target_weight += proposal.total_weight;
+    let delegators = DELEGATORS
+        .prefix((app_id, &delegate))
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count();
+    if delegators >= MAX_DELEGATORS {
+        return Err(ContractError::TooManyDelegators {});
     }
-    let ratio = Decimal::raw(total_votes_weight).div(Decimal::raw(total_target_weight));
 
-    participation_info.current_supply = Uint128::from(total_weight).u128();
-    participation_info.active_participation_supply = total_votes_weight;
-    let response = AppGovConfigResponse {
-        proposal_count: participation_info.proposal_count,
-        current_supply: participation_info.current_supply,
-        active_participation: ratio,
-    };
-    Ok(response)
+    DELEGATIONS.save(deps.storage, (app_id, &info.sender), &delegate)?;
+    DELEGATORS.save(deps.storage, (app_id, &delegate, &info.sender), &Empty {})?;
+
+    Ok(Response::new()
+        .add_attribute("action", "delegate")
+        .add_attribute("delegator", info.sender)
+        .add_attribute("delegate", delegate)
+        .add_attribute("app_id", app_id.to_string()))
 }
 
-fn reverse_proposals(
-    deps: Deps<ComdexQuery>,
-    env: Env,
-    start_before: Option<u64>,
-    limit: Option<u32>,
-) -> StdResult<ProposalListResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let end = start_before.map(Bound::exclusive);
-    let props: StdResult<Vec<_>> = PROPOSALS
-        .range(deps.storage, None, end, Order::Descending)
-        .take(limit)
-        .map(|p| map_proposal(&env.block, p))
-        .collect();
+pub fn execute_undelegate(
+    deps: DepsMut<ComdexQuery>,
+    _env: Env,
+    info: MessageInfo,
+    app_id: u64,
+) -> Result<Response<ComdexMessages>, ContractError> {
+    if !info.funds.is_empty() {
+        return Err(ContractError::CustomError {
+            val: "Funds deposit not allowed".to_string(),
+        });
+    }
+    let delegate = DELEGATIONS
+        .may_load(deps.storage, (app_id, &info.sender))?
+        .ok_or(ContractError::NoDelegation {})?;
 
-    Ok(ProposalListResponse { proposals: props? })
+    // votes already cast by the delegate keep the delegated weight
+    DELEGATIONS.remove(deps.storage, (app_id, &info.sender));
+    DELEGATORS.remove(deps.storage, (app_id, &delegate, &info.sender));
+
+    Ok(Response::new()
+        .add_attribute("action", "undelegate")
+        .add_attribute("delegator", info.sender)
+        .add_attribute("delegate", delegate)
+        .add_attribute("app_id", app_id.to_string()))
 }
 
-fn map_proposal(
-    block: &BlockInfo,
-    item: StdResult<(u64, Proposal)>,
-) -> StdResult<ProposalResponse> {
-    item.map(|(id, prop)| {
-        let status = prop.current_status(block);
-        let threshold = prop.threshold.to_response(prop.total_weight);
-        ProposalResponse {
-            id,
-            title: prop.title,
-            description: prop.description,
-            msgs: prop.msgs,
+/// Sums the power of the delegate's delegators that have not voted on the proposal
+/// themselves. Each counted delegator is recorded so a later direct vote can override it.
+fn collect_delegated_power(
+    deps: DepsMut<ComdexQuery>,
+    cfg: &Config,
+    proposal_id: u64,
+    prop: &Proposal,
+    delegate: &Addr,
+) -> StdResult<u128> {
+    let delegators = DELEGATORS
+        .prefix((prop.app_mapping_id, delegate))
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    let locking = LockingContract::new(cfg.locking_contract.clone());
+    let mut delegated_weight: u128 = 0;
+    for delegator in delegators {
+        // already counted by a direct vote, or by a delegate before a re-delegation
+        if BALLOTS.has(deps.storage, (proposal_id, &delegator))
+            || DELEGATEDVOTES.has(deps.storage, (proposal_id, &delegator))
+        {
+            continue;
+        }
+        let power = locking
+            .voting_power(
+                &deps.querier,
+                &delegator,
+                &prop.token_denom,
+                Some(prop.start_height),
+            )?
+            .u128();
+        if power == 0 {
+            continue;
+        }
+        DELEGATEDVOTES.save(
+            deps.storage,
+            (proposal_id, &delegator),
+            &DelegatedVote {
+                delegate: delegate.clone(),
+                weight: power,
+            },
+        )?;
+        delegated_weight += power;
+    }
+    Ok(delegated_weight)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<ComdexQuery>, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Threshold { proposal_id } => to_binary(&query_threshold(deps, proposal_id)?),
+        QueryMsg::Proposal { proposal_id } => {
+            to_binary(&query_proposal_detailed(deps, env, proposal_id)?)
+        }
+        QueryMsg::Vote { proposal_id, voter } => to_binary(&query_vote(deps, proposal_id, voter)?),
+        QueryMsg::ListProposals { start_after, limit } => {
+            to_binary(&list_proposals(deps, env, start_after, limit)?)
+        }
+        QueryMsg::ReverseProposals {
+            start_before,
+            limit,
+        } => to_binary(&reverse_proposals(deps, env, start_before, limit)?),
+        QueryMsg::ListVotes {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_binary(&list_votes(deps, proposal_id, start_after, limit)?),
+        QueryMsg::ListAppProposal {
+            app_id,
+            start_after,
+            start_before,
+            limit,
+            ascending,
             status,
-            expires: prop.expires,
-            threshold,
+            proposer,
+            start_time_from,
+            start_time_to,
+        } => to_binary(&get_proposals_by_app(
+            deps,
+            env,
+            app_id,
+            AppProposalQuery {
+                start_after,
+                start_before,
+                limit,
+                ascending,
+                status,
+                proposer,
+                start_time_from,
+                start_time_to,
+            },
+        )?),
+        QueryMsg::Supply { denom } => to_binary(&query_supply(deps, denom)?),
+        QueryMsg::TotalVTokens {
+            address,
+            denom,
+            height,
+        } => to_binary(&query_total_vtokens(deps, address, denom, height)?),
+        QueryMsg::AppAllUpData { app_id } => to_binary(&get_all_up_info_by_app(deps, env, app_id)?),
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::ParticipationStats { app_id } => {
+            to_binary(&get_all_up_info_by_app_ratio(deps, env, app_id)?)
         }
-    })
+        QueryMsg::AppGovParams { app_id } => to_binary(&query_app_gov_params(deps, app_id)?),
+        QueryMsg::Delegation { delegator, app_id } => {
+            to_binary(&query_delegation(deps, delegator, app_id)?)
+        }
+        QueryMsg::DelegatedPower {
+            delegate,
+            app_id,
+            height,
+        } => to_binary(&query_delegated_power(deps, delegate, app_id, height)?),
+        QueryMsg::ProposalsByProposer {
+            proposer,
+            start_after,
+            limit,
+        } => to_binary(&list_proposals_by_proposer(
+            deps,
+            env,
+            proposer,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::VotesByVoter {
+            voter,
+            app_id,
+            start_after,
+            limit,
+        } => to_binary(&list_votes_by_voter(
+            deps,
+            voter,
+            app_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::VoteAudit { proposal_id } => to_binary(&query_vote_audit(deps, proposal_id)?),
+        QueryMsg::AllowedContracts { app_id } => to_binary(&query_allowed_contracts(deps, app_id)?),
+        QueryMsg::Treasury { app_id } => to_binary(&query_treasury(deps, app_id)?),
+        QueryMsg::Deposit {
+            proposal_id,
+            depositor,
+        } => to_binary(&query_deposit(deps, proposal_id, depositor)?),
+        QueryMsg::ListDeposits {
+            proposal_id,
+            start_after,
+            limit,
+        } => to_binary(&list_deposits(deps, proposal_id, start_after, limit)?),
+    }
 }
 
-fn query_vote(deps: Deps<ComdexQuery>, proposal_id: u64, voter: String) -> StdResult<VoteResponse> {
-    let voter = deps.api.addr_validate(&voter)?;
-    let ballot = BALLOTS.may_load(deps.storage, (proposal_id, &voter))?;
-    let vote = ballot.map(|b| VoteInfo {
-        proposal_id,
-        voter: voter.into(),
-        vote: b.vote,
-        weight: b.weight,
-    });
-    Ok(VoteResponse { vote })
+fn query_threshold(deps: Deps<ComdexQuery>, proposal_id: u64) -> StdResult<ThresholdResponse> {
+    let prop = proposals().load(deps.storage, proposal_id)?;
+
+    Ok(prop.threshold.to_response(prop.total_weight))
 }
 
-fn list_votes(
-    deps: Deps<ComdexQuery>,
-    proposal_id: u64,
-    start_after: Option<String>,
-    limit: Option<u32>,
-) -> StdResult<VoteListResponse> {
-    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+fn query_config(deps: Deps<ComdexQuery>) -> StdResult<Config> {
+    let cfg = CONFIG.load(deps.storage)?;
 
-    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+    Ok(cfg)
+}
 
-    let votes = BALLOTS
-        .prefix(proposal_id)
-        .range(deps.storage, start, None, Order::Ascending)
-        .take(limit)
-        .map(|item| {
-            item.map(|(addr, ballot)| VoteInfo {
-                proposal_id,
-                voter: addr.into(),
-                vote: ballot.vote,
-                weight: ballot.weight,
-            })
-        })
-        .collect::<StdResult<_>>()?;
+fn query_app_gov_params(deps: Deps<ComdexQuery>, app_id: u64) -> StdResult<AppGovParams> {
+    let params = APPGOVPARAMS
+        .may_load(deps.storage, app_id)?
+        .unwrap_or_default();
+    Ok(params)
+}
 
-    Ok(VoteListResponse { votes })
+// proxied to the locking contract
+fn query_supply(deps: Deps<ComdexQuery>, denom: String) -> StdResult<TokenSupply> {
+    let cfg = CONFIG.load(deps.storage)?;
+    LockingContract::new(cfg.locking_contract).supply(&deps.querier, denom)
 }
 
-#[entry_point]
-pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
-    let ver = cw2::get_contract_version(deps.storage)?;
-    // ensure we are migrating from an allowed contract
-    if ver.contract != CONTRACT_NAME {
-        return Err(StdError::generic_err("Can only upgrade from same type").into());
-    }
-    // note: better to do proper semver compare, but string compare *usually* works
-    if ver.version.as_str() > CONTRACT_VERSION {
-        return Err(StdError::generic_err("Cannot upgrade from a newer version").into());
+// proxied to the locking contract
+fn query_total_vtokens(
+    deps: Deps<ComdexQuery>,
+    address: Addr,
+    denom: String,
+    height: Option<u64>,
+) -> StdResult<Uint128> {
+    let address = deps.api.addr_validate(address.as_str())?;
+    let cfg = CONFIG.load(deps.storage)?;
+    LockingContract::new(cfg.locking_contract).voting_power(&deps.querier, &address, denom, height)
+}
+
+fn query_allowed_contracts(
+    deps: Deps<ComdexQuery>,
+    app_id: u64,
+) -> StdResult<AllowedContractsResponse> {
+    let contracts = ALLOWEDCONTRACTS
+        .prefix(app_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+    Ok(AllowedContractsResponse { contracts })
+}
+
+fn query_treasury(deps: Deps<ComdexQuery>, app_id: u64) -> StdResult<TreasuryResponse> {
+    let funds = APPTREASURY
+        .prefix(app_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(denom, amount)| Coin { denom, amount }))
+        .collect::<StdResult<Vec<Coin>>>()?;
+    Ok(TreasuryResponse { funds })
+}
+
+fn query_delegation(
+    deps: Deps<ComdexQuery>,
+    delegator: String,
+    app_id: u64,
+) -> StdResult<DelegationResponse> {
+    let delegator = deps.api.addr_validate(&delegator)?;
+    let delegate = DELEGATIONS.may_load(deps.storage, (app_id, &delegator))?;
+    Ok(DelegationResponse { delegate })
+}
+
+fn query_delegated_power(
+    deps: Deps<ComdexQuery>,
+    delegate: String,
+    app_id: u64,
+    height: Option<u64>,
+) -> StdResult<DelegatedPowerResponse> {
+    let delegate = deps.api.addr_validate(&delegate)?;
+    let cfg = CONFIG.load(deps.storage)?;
+    let app_response = query_app_exists(deps, app_id)?;
+    let gov_token_denom = query_get_asset_data(deps, app_response.gov_token_id)?;
+
+    let delegators = DELEGATORS
+        .prefix((app_id, &delegate))
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    let locking = LockingContract::new(cfg.locking_contract);
+    let mut power: u128 = 0;
+    for delegator in delegators.iter() {
+        power += locking
+            .voting_power(&deps.querier, delegator, &gov_token_denom, height)?
+            .u128();
     }
+    Ok(DelegatedPowerResponse {
+        delegators: delegators.len() as u64,
+        power,
+    })
+}
 
-    let msg = BankMsg::Send {
-        to_address: msg.refund_address.to_string(),
-        amount: vec![Coin{amount:Uint128::from(10000000000_u128),denom: "uharbor".to_string()}],
-    };
+fn query_proposal_detailed(
+    deps: Deps<ComdexQuery>,
+    env: Env,
+    id: u64,
+) -> StdResult<ProposalResponseTotal> {
+    let prop = proposals().load(deps.storage, id)?;
+    Ok(proposal_response(&env.block, id, prop))
+}
 
-    let cosmos_msg = CosmosMsg::Bank(msg);
+fn proposal_response(block: &BlockInfo, id: u64, prop: Proposal) -> ProposalResponseTotal {
+    let status = prop.current_status(block);
+    let eta = prop.eta();
+    ProposalResponseTotal {
+        id,
+        title: prop.title,
+        description: prop.description,
+        msgs: prop.msgs,
+        status,
+        start_time: prop.start_time,
+        expires: prop.expires,
+        votes: prop.votes,
+        duration: prop.duration,
+        start_height: prop.start_height,
+        threshold: prop.threshold,
+        veto_threshold: prop.veto_threshold,
+        proposer: prop.proposer,
+        token_denom: prop.token_denom,
+        total_weight: prop.total_weight,
+        current_deposit: prop.current_deposit,
+        refunded_deposit: prop.refunded_deposit,
+        slashed_deposit: prop.slashed_deposit,
+        end_time: prop.end_time,
+        eta,
+        cosmos_msgs: prop.cosmos_msgs,
+        actions: prop.actions,
+        choices: prop.choices,
+        choice_votes: prop.choice_votes,
+    }
+}
 
-    // set the new version
-    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+// settings for pagination
+const MAX_LIMIT: u32 = 300;
+const DEFAULT_LIMIT: u32 = 100;
+// proposals read by one filtered listing, matching or not
+const MAX_SCAN: usize = 1000;
 
-    // do any desired state migrations...
+fn list_proposals(
+    deps: Deps<ComdexQuery>,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let props = proposals()
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|p| map_proposal(&env.block, p))
+        .collect::<StdResult<_>>()?;
 
-    Ok(Response::new().add_message(cosmos_msg))
+    Ok(ProposalListResponse { proposals: props })
 }
 
-#[cfg(test)]
-#[allow(unused_variables)]
-mod tests {
-    use crate::msg;
-    use cosmwasm_std::testing::{mock_env, mock_info};
-    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
-    use cosmwasm_std::{coins, Addr, OwnedDeps};
-    use cosmwasm_std::{Decimal, Timestamp};
-    use cw_storage_plus::Map;
-    use cw_utils::Expiration;
-    use cw_utils::{Duration, Threshold};
-    use std::marker::PhantomData;
+// pagination and filters of `ListAppProposal`
+struct AppProposalQuery {
+    start_after: Option<u64>,
+    start_before: Option<u64>,
+    limit: Option<u32>,
+    ascending: bool,
+    status: Option<Status>,
+    proposer: Option<String>,
+    start_time_from: Option<Timestamp>,
+    start_time_to: Option<Timestamp>,
+}
 
-    use super::*;
+fn list_proposals_by_proposer(
+    deps: Deps<ComdexQuery>,
+    env: Env,
+    proposer: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let proposer = deps.api.addr_validate(&proposer)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+    let props = proposals()
+        .idx
+        .proposer
+        .prefix(proposer.into_string())
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|p| map_proposal(&env.block, p))
+        .collect::<StdResult<_>>()?;
 
-    const OWNER: &str = "admin0001";
+    Ok(ProposalListResponse { proposals: props })
+}
 
-    pub fn mock_dependencies1() -> OwnedDeps<MockStorage, MockApi, MockQuerier, ComdexQuery> {
-        OwnedDeps {
-            storage: MockStorage::default(),
-            api: MockApi::default(),
-            querier: MockQuerier::default(),
-            custom_query_type: PhantomData,
+fn get_proposals_by_app(
+    deps: Deps<ComdexQuery>,
+    env: Env,
+    app_id: u64,
+    filter: AppProposalQuery,
+) -> StdResult<AppProposalResponse> {
+    let limit = filter.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let min = filter.start_after.map(Bound::exclusive);
+    let max = filter.start_before.map(Bound::exclusive);
+    let order = if filter.ascending {
+        Order::Ascending
+    } else {
+        Order::Descending
+    };
+
+    // only the proposals of the app, of the proposer, or with a matching stored status are read
+    let props: ProposalIter = match (filter.proposer, filter.status) {
+        (Some(proposer), _) => {
+            let proposer = deps.api.addr_validate(&proposer)?;
+            Box::new(
+                proposals()
+                    .idx
+                    .app_proposer
+                    .prefix((app_id, proposer.into_string()))
+                    .range(deps.storage, min, max, order),
+            )
         }
-    }
+        (None, Some(status)) => {
+            let sources = stored_statuses(status)
+                .into_iter()
+                .map(|stored| {
+                    proposals().idx.status.prefix((app_id, stored as u8)).range(
+                        deps.storage,
+                        min.clone(),
+                        max.clone(),
+                        order,
+                    )
+                })
+                .collect();
+            Box::new(merge_by_id(sources, order))
+        }
+        (None, None) => Box::new(proposals().idx.app.prefix(app_id).range(
+            deps.storage,
+            min,
+            max,
+            order,
+        )),
+    };
 
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies1();
+    // at most MAX_SCAN proposals are read, `next` resumes where reading stopped
+    let mut props = props.peekable();
+    let mut app_proposals = vec![];
+    let mut next = None;
+    let mut scanned = 0;
+    while let Some(item) = props.next() {
+        let (id, prop) = item?;
+        scanned += 1;
+        let in_time = filter
+            .start_time_from
+            .is_none_or(|from| prop.start_time >= from)
+            && filter.start_time_to.is_none_or(|to| prop.start_time <= to);
+        if in_time {
+            let proposal = proposal_response(&env.block, id, prop);
+            if filter.status.is_none_or(|status| status == proposal.status) {
+                app_proposals.push(proposal);
+            }
+        }
+        if app_proposals.len() == limit || scanned == MAX_SCAN {
+            if props.peek().is_some() {
+                next = Some(id);
+            }
+            break;
+        }
+    }
 
-        // Empty locking contract address should not be permitted
-        let msg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::one(),
-                quorum: Decimal::one(),
-            },
-            locking_contract: Addr::unchecked(""),
-        };
-        let result =
-            instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap_err();
-        match result {
-            ContractError::Std(StdError::GenericErr { .. }) => {}
-            e => panic!("{:?}", e),
-        };
+    let proposal_count = APPGOVCONFIG
+        .may_load(deps.storage, app_id)?
+        .map_or(0, |config| config.proposal_count);
 
-        const OWNER: &str = "admin001";
-        let _addr1 = Addr::unchecked("addr1");
-        let _addr2 = Addr::unchecked("addr2");
-        let info = mock_info(OWNER, &[]);
+    Ok(AppProposalResponse {
+        proposals: app_proposals,
+        proposal_count,
+        next,
+    })
+}
 
-        let not_acceptable_msg1 = InstantiateMsg {
-            threshold: Threshold::AbsoluteCount { weight: 10 },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
+// stored statuses of the proposals `current_status` reports as `status`
+fn stored_statuses(status: Status) -> Vec<Status> {
+    match status {
+        Status::Passed => vec![Status::Open, Status::Passed],
+        Status::Rejected => vec![Status::Open, Status::Rejected],
+        Status::DepositFailed => vec![Status::Pending, Status::DepositFailed],
+        status => vec![status],
+    }
+}
 
-        let not_acceptable_msg2 = InstantiateMsg {
-            threshold: Threshold::AbsolutePercentage {
-                percentage: Decimal::percent(50),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
+type ProposalIter<'a> = Box<dyn Iterator<Item = StdResult<(u64, Proposal)>> + 'a>;
+
+/// Merges iterators that are each sorted by proposal id in `order` into one.
+fn merge_by_id<'a>(
+    sources: Vec<ProposalIter<'a>>,
+    order: Order,
+) -> impl Iterator<Item = StdResult<(u64, Proposal)>> + 'a {
+    let mut sources: Vec<_> = sources.into_iter().map(Iterator::peekable).collect();
+    std::iter::from_fn(move || {
+        let mut pick: Option<(usize, u64)> = None;
+        for (i, source) in sources.iter_mut().enumerate() {
+            match source.peek() {
+                Some(Ok((id, _))) => {
+                    let first = pick.is_none_or(|(_, best)| match order {
+                        Order::Ascending => *id < best,
+                        Order::Descending => *id > best,
+                    });
+                    if first {
+                        pick = Some((i, *id));
+                    }
+                }
+                Some(Err(_)) => return source.next(),
+                None => {}
+            }
+        }
+        pick.and_then(|(i, _)| sources[i].next())
+    })
+}
 
-        let expected_msg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
+fn get_all_up_info_by_app(
+    deps: Deps<ComdexQuery>,
+    env: Env,
+    app_id: u64,
+) -> StdResult<AppGovConfig> {
+    let info = proposals()
+        .idx
+        .app
+        .prefix(app_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
 
-        let res1 = instantiate(deps.as_mut(), mock_env(), info.clone(), not_acceptable_msg1);
-        assert_eq!(res1, Err(ContractError::AbsoluteCountNotAccepted {}));
+    let app_response = query_app_exists(deps, app_id)?;
+    let gov_token_id = app_response.gov_token_id;
+    let total_weight = get_token_supply(deps, app_id, gov_token_id)?;
 
-        let res2 = instantiate(deps.as_mut(), mock_env(), info.clone(), not_acceptable_msg2);
-        assert_eq!(res2, Err(ContractError::AbsolutePercentageNotAccepted {}));
+    let mut participation_info = APPGOVCONFIG.may_load(deps.storage, app_id)?.unwrap();
+    let mut total_votes_weight: u128 = 0;
 
-        let res3 = instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg);
-        assert_ne!(res3, Err(ContractError::AbsoluteCountNotAccepted {}));
-        assert_ne!(res3, Err(ContractError::AbsolutePercentageNotAccepted {}));
-        assert_ne!(res3, Err(ContractError::InvalidThreshold {}));
-        assert_ne!(res3, Err(ContractError::ZeroQuorumThreshold {}));
-        assert_ne!(res3, Err(ContractError::UnreachableQuorumThreshold {}));
+    for i in info {
+        let proposal = query_proposal_detailed(deps, env.clone(), i)?;
+        total_votes_weight += proposal.votes.total();
     }
-    // Propose Testcase
-    #[test]
-    fn test_propose() {
-        let mut deps = mock_dependencies1();
-        //let mut deps2=mock_dependencies1();
-        let info = mock_info(OWNER, &[]);
-        let msgs_com = vec![
-            ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 },
-            ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 34 },
-        ];
+    participation_info.current_supply = Uint128::from(total_weight).u128();
+    participation_info.active_participation_supply = total_votes_weight;
+    participation_info.proposal_count += 1;
+    Ok(participation_info)
+}
 
-        let propose_1 = Propose {
-            title: "propose".to_string(),
-            description: "test_propose".to_string(),
-            msgs: msgs_com,
-            // note: we ignore API-spec'd earliest if passed, always opens immediately
-            latest: Some(Expiration::Never {}),
-            app_id_param: 33,
-        };
+fn get_all_up_info_by_app_ratio(
+    deps: Deps<ComdexQuery>,
+    env: Env,
+    app_id: u64,
+) -> StdResult<AppGovConfigResponse> {
+    let info = proposals()
+        .idx
+        .app
+        .prefix(app_id)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
 
-        //let msgs_length=msgs_com.len();
-        let k = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose_1);
-        let msgs_2: Vec<ComdexMessages> = vec![];
-        let propose_2 = Propose {
-            title: "propose".to_string(),
-            description: "test_propose".to_string(),
-            msgs: msgs_2,
-            // note: we ignore API-spec'd earliest if passed, always opens immediately
-            latest: Some(Expiration::Never {}),
-            app_id_param: 33,
-        };
-        let f = execute_propose(deps.as_mut(), mock_env(), info, propose_2);
+    let app_response = query_app_exists(deps, app_id)?;
+    let gov_token_id = app_response.gov_token_id;
+    let total_weight = get_token_supply(deps, app_id, gov_token_id)?;
+
+    let mut participation_info = APPGOVCONFIG.may_load(deps.storage, app_id)?.unwrap();
+    let mut total_votes_weight: u128 = 0;
+    let mut total_target_weight: u128 = 0;
+
+    for i in info {
+        let proposal = query_proposal_detailed(deps, env.clone(), i)?;
+        total_votes_weight += proposal.votes.total();
+        total_target_weight += proposal.total_weight;
     }
+    let ratio = Decimal::raw(total_votes_weight).div(Decimal::raw(total_target_weight));
 
-    //Execute Testcase
-    #[test]
-    fn test_execute() {
-        let mut deps = mock_dependencies1();
-        let info = mock_info(OWNER, &[]);
+    participation_info.current_supply = Uint128::from(total_weight).u128();
+    participation_info.active_participation_supply = total_votes_weight;
+    let response = AppGovConfigResponse {
+        proposal_count: participation_info.proposal_count,
+        current_supply: participation_info.current_supply,
+        active_participation: ratio,
+    };
+    Ok(response)
+}
+
+fn reverse_proposals(
+    deps: Deps<ComdexQuery>,
+    env: Env,
+    start_before: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ProposalListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let end = start_before.map(Bound::exclusive);
+    let props: StdResult<Vec<_>> = proposals()
+        .range(deps.storage, None, end, Order::Descending)
+        .take(limit)
+        .map(|p| map_proposal(&env.block, p))
+        .collect();
+
+    Ok(ProposalListResponse { proposals: props? })
+}
+
+fn map_proposal(
+    block: &BlockInfo,
+    item: StdResult<(u64, Proposal)>,
+) -> StdResult<ProposalResponse> {
+    item.map(|(id, prop)| {
+        let status = prop.current_status(block);
+        let threshold = prop.threshold.to_response(prop.total_weight);
+        ProposalResponse {
+            id,
+            title: prop.title,
+            description: prop.description,
+            msgs: prop.msgs,
+            status,
+            expires: prop.expires,
+            threshold,
+        }
+    })
+}
+
+fn query_vote(deps: Deps<ComdexQuery>, proposal_id: u64, voter: String) -> StdResult<VoteResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let ballot = BALLOTS.may_load(deps.storage, (proposal_id, &voter))?;
+    let vote = ballot.map(|b| VoteInfo {
+        proposal_id,
+        voter: voter.into(),
+        vote: b.vote,
+        weight: b.weight,
+        options: split_options(&b),
+    });
+    Ok(VoteResponse { vote })
+}
+
+// recomputes the tally of a proposal from all of its ballots
+fn query_vote_audit(deps: Deps<ComdexQuery>, proposal_id: u64) -> StdResult<VoteAuditResponse> {
+    let prop = proposals().load(deps.storage, proposal_id)?;
+
+    let mut recount = prop.clone();
+    recount.votes = Votes::yes(0);
+    recount.choice_votes = vec![0; prop.choices.len()];
+    let mut ballots = 0;
+    for item in BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, None, None, Order::Ascending)
+    {
+        let (_, ballot) = item?;
+        recount.add_ballot(&ballot);
+        ballots += 1;
+    }
+
+    Ok(VoteAuditResponse {
+        ballots,
+        matches: recount.votes == prop.votes && recount.choice_votes == prop.choice_votes,
+        stored: prop.votes,
+        computed: recount.votes,
+        stored_choice_votes: prop.choice_votes,
+        computed_choice_votes: recount.choice_votes,
+    })
+}
+
+// weight of each option for split ballots
+fn split_options(ballot: &Ballot) -> Vec<(Vote, u128)> {
+    if ballot.options.is_empty() {
+        vec![]
+    } else {
+        ballot.vote_weight().options()
+    }
+}
+
+fn list_votes(
+    deps: Deps<ComdexQuery>,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<VoteListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+    let votes = BALLOTS
+        .prefix(proposal_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(addr, ballot)| VoteInfo {
+                proposal_id,
+                voter: addr.into(),
+                vote: ballot.vote,
+                weight: ballot.weight,
+                options: split_options(&ballot),
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(VoteListResponse { votes })
+}
+
+fn query_deposit(
+    deps: Deps<ComdexQuery>,
+    proposal_id: u64,
+    depositor: String,
+) -> StdResult<DepositResponse> {
+    let depositor = deps.api.addr_validate(&depositor)?;
+    let prop = proposals().may_load(deps.storage, proposal_id)?;
+    let deposit = VOTERDEPOSIT
+        .may_load(deps.storage, (proposal_id, &depositor))?
+        .map(|amount| deposit_info(prop.as_ref(), proposal_id, depositor, amount));
+    Ok(DepositResponse { deposit })
+}
+
+// deposits of a slashed proposal only keep their refundable part
+fn deposit_info(
+    prop: Option<&Proposal>,
+    proposal_id: u64,
+    depositor: Addr,
+    amount: Vec<Coin>,
+) -> DepositInfo {
+    let (amount, slashed) = match prop {
+        Some(prop) if prop.is_slashed => prop.split_deposit(&amount),
+        _ => (amount, vec![]),
+    };
+    DepositInfo {
+        proposal_id,
+        depositor: depositor.into(),
+        amount,
+        slashed,
+    }
+}
+
+fn list_deposits(
+    deps: Deps<ComdexQuery>,
+    proposal_id: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<DepositListResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+    let prop = proposals().may_load(deps.storage, proposal_id)?;
+
+    let deposits = VOTERDEPOSIT
+        .prefix(proposal_id)
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(depositor, amount)| {
+                deposit_info(prop.as_ref(), proposal_id, depositor, amount)
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(DepositListResponse { deposits })
+}
+
+fn list_votes_by_voter(
+    deps: Deps<ComdexQuery>,
+    voter: String,
+    app_id: u64,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<VoteListResponse> {
+    let voter = deps.api.addr_validate(&voter)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let votes = VOTERBALLOTS
+        .prefix((&voter, app_id))
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|proposal_id| {
+            let proposal_id = proposal_id?;
+            let ballot = BALLOTS.load(deps.storage, (proposal_id, &voter))?;
+            Ok(VoteInfo {
+                proposal_id,
+                voter: voter.to_string(),
+                vote: ballot.vote,
+                weight: ballot.weight,
+                options: split_options(&ballot),
+            })
+        })
+        .collect::<StdResult<_>>()?;
+
+    Ok(VoteListResponse { votes })
+}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let ver = cw2::get_contract_version(deps.storage)?;
+    // ensure we are migrating from an allowed contract
+    if ver.contract != CONTRACT_NAME {
+        return Err(StdError::generic_err("Can only upgrade from same type").into());
+    }
+    // note: better to do proper semver compare, but string compare *usually* works
+    if ver.version.as_str() > CONTRACT_VERSION {
+        return Err(StdError::generic_err("Cannot upgrade from a newer version").into());
+    }
+
+    let msg = BankMsg::Send {
+        to_address: msg.refund_address.to_string(),
+        amount: vec![Coin {
+            amount: Uint128::from(10000000000_u128),
+            denom: "uharbor".to_string(),
+        }],
+    };
+
+    let cosmos_msg = CosmosMsg::Bank(msg);
+
+    // set the new version
+    cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // do any desired state migrations...
+    migrate_proposal_indexes(deps.storage)?;
+    migrate_slashable_deposits(deps.storage)?;
+    migrate_escrowed_deposits(deps.storage)?;
+
+    Ok(Response::new().add_message(cosmos_msg))
+}
+
+// builds the indexes of `proposals()` and drops the per-app lists they replace
+fn migrate_proposal_indexes(storage: &mut dyn Storage) -> StdResult<()> {
+    let all_proposals = proposals()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, prop) in all_proposals {
+        proposals().replace(storage, id, Some(&prop), None)?;
+        let voters = BALLOTS
+            .prefix(id)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<Addr>>>()?;
+        for voter in voters {
+            VOTERBALLOTS.save(storage, (&voter, prop.app_mapping_id, id), &Empty {})?;
+        }
+    }
+
+    let app_ids = PROPOSALSBYAPP
+        .keys(storage, None, None, Order::Ascending)
+        .chain(APPPROPOSALS.keys(storage, None, None, Order::Ascending))
+        .collect::<StdResult<Vec<u64>>>()?;
+    for app_id in app_ids {
+        PROPOSALSBYAPP.remove(storage, app_id);
+        APPPROPOSALS.remove(storage, app_id);
+    }
+    Ok(())
+}
+
+// proposals stored before the per depositor rounding slash their total deposit
+fn migrate_slashable_deposits(storage: &mut dyn Storage) -> StdResult<()> {
+    let all_proposals = proposals()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (id, mut prop) in all_proposals {
+        if prop.is_slashed || prop.slashable_deposit != 0 {
+            continue;
+        }
+        let total: Uint128 = prop
+            .deposit
+            .iter()
+            .filter(|coin| coin.denom == prop.token_denom)
+            .map(|coin| coin.amount)
+            .sum();
+        prop.slashable_deposit = prop.slashed_part(total);
+        proposals().save(storage, id, &prop)?;
+    }
+    Ok(())
+}
+
+// sums up the deposits not refunded yet of the proposals that were not slashed
+fn migrate_escrowed_deposits(storage: &mut dyn Storage) -> StdResult<()> {
+    let all_proposals = proposals()
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    let mut escrowed: BTreeMap<String, Uint128> = BTreeMap::new();
+    for (id, prop) in all_proposals {
+        // refunds only removed the deposit of the depositor, not `current_deposit`,
+        // and slashes burnt the whole deposit
+        if prop.is_slashed {
+            continue;
+        }
+        let deposits = VOTERDEPOSIT
+            .prefix(id)
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for coin in deposits.into_iter().flat_map(|(_, deposit)| deposit) {
+            *escrowed.entry(coin.denom).or_default() += coin.amount;
+        }
+    }
+    for (denom, held) in escrowed {
+        ESCROWEDDEPOSITS.save(storage, &denom, &held)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(unused_variables)]
+mod tests {
+    use crate::helpers::GovernanceContract;
+    use crate::locking::LockingQueryMsg;
+    use crate::msg;
+    use crate::msg::ProposalMsg;
+    use cosmwasm_std::testing::{mock_env, mock_info};
+    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{coin, coins, from_binary, Addr, ContractResult, OwnedDeps, SystemResult};
+    use cosmwasm_std::{Decimal, Timestamp};
+    use cosmwasm_std::{QuerierResult, QuerierWrapper, WasmMsg, WasmQuery};
+    use cw_storage_plus::Map;
+    use cw_utils::Expiration;
+    use cw_utils::{Duration, Threshold, ThresholdResponse};
+    use std::marker::PhantomData;
+
+    use super::*;
+
+    const OWNER: &str = "admin0001";
+
+    pub fn mock_dependencies1() -> OwnedDeps<MockStorage, MockApi, MockQuerier, ComdexQuery> {
+        OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier: MockQuerier::default(),
+            custom_query_type: PhantomData,
+        }
+    }
+
+    // app 1 with gov token "ugov", a min deposit of 100 and a voting period of 100 seconds,
+    // the locking contract reports a supply of 100 vtokens
+    pub fn mock_app_dependencies(
+    ) -> OwnedDeps<MockStorage, MockApi, MockQuerier<ComdexQuery>, ComdexQuery> {
+        let mut querier =
+            MockQuerier::<ComdexQuery>::new(&[]).with_custom_handler(|query| match query {
+                // app 1 governs with ugov, any other app with its own token
+                ComdexQuery::GetApp { app_id } => SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&comdex_bindings::GetAppResponse {
+                        min_gov_deposit: "100".to_string(),
+                        gov_time_in_seconds: 100,
+                        gov_token_id: *app_id,
+                    })
+                    .unwrap(),
+                )),
+                ComdexQuery::GetAssetData { asset_id } => {
+                    let denom = match asset_id {
+                        1 => "ugov".to_string(),
+                        id => format!("uapp{}", id),
+                    };
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&comdex_bindings::GetAssetDataResponse { denom }).unwrap(),
+                    ))
+                }
+                _ => SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&comdex_bindings::MessageValidateResponse {
+                        found: true,
+                        err: String::new(),
+                    })
+                    .unwrap(),
+                )),
+            });
+        querier.update_wasm(mock_locking_query);
+        let mut deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier,
+            custom_query_type: PhantomData,
+        };
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            InstantiateMsg {
+                threshold: Threshold::ThresholdQuorum {
+                    threshold: Decimal::percent(50),
+                    quorum: Decimal::percent(33),
+                },
+                locking_contract: Addr::unchecked("locking-contract"),
+                veto_threshold: None,
+            },
+        )
+        .unwrap();
+        deps
+    }
+
+    fn app_proposal(msgs: Vec<ComdexMessages>) -> Propose {
+        Propose {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            msgs,
+            latest: None,
+            app_id_param: 1,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+        }
+    }
+
+    // Open proposal of app 1 that never expires, tests override the fields they care about
+    fn test_proposal() -> Proposal {
+        let env = mock_env();
+        Proposal {
+            title: "title".to_string(),
+            start_time: env.block.time,
+            description: "description".to_string(),
+            start_height: env.block.height,
+            expires: Expiration::Never {},
+            msgs: vec![],
+            status: Status::Open,
+            duration: Duration::Time(100),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 100,
+            votes: Votes::yes(0),
+            deposit: vec![],
+            proposer: OWNER.to_string(),
+            token_denom: "denom1".to_string(),
+            min_deposit: Uint128::from(100_u64),
+            current_deposit: 100,
+            app_mapping_id: 1,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: false,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        }
+    }
+
+    // locking contract with a supply of 100 vtokens and a fixed balance per address
+    fn mock_locking_query(query: &WasmQuery) -> QuerierResult {
+        match query {
+            WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+                LockingQueryMsg::Supply { .. } => SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&TokenSupply {
+                        token: 100,
+                        vtoken: 100,
+                    })
+                    .unwrap(),
+                )),
+                // after the mock_env height delegator1 unlocked and delegator3 locked
+                LockingQueryMsg::TotalVTokens {
+                    address, height, ..
+                } => {
+                    let later = height.is_some_and(|height| height > 12345);
+                    let power: u128 = match address.as_str() {
+                        "delegate" => 10,
+                        "delegator1" if later => 5,
+                        "delegator1" => 20,
+                        "delegator2" => 30,
+                        "delegator3" if later => 40,
+                        _ => 0,
+                    };
+                    SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&Uint128::from(power)).unwrap(),
+                    ))
+                }
+            },
+            q => panic!("unexpected query {:?}", q),
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies1();
+
+        // Empty locking contract address should not be permitted
+        let msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::one(),
+                quorum: Decimal::one(),
+            },
+            locking_contract: Addr::unchecked(""),
+            veto_threshold: None,
+        };
+        let result =
+            instantiate(deps.as_mut(), mock_env(), mock_info("sender", &[]), msg).unwrap_err();
+        match result {
+            ContractError::Std(StdError::GenericErr { .. }) => {}
+            e => panic!("{:?}", e),
+        };
+
+        const OWNER: &str = "admin001";
+        let _addr1 = Addr::unchecked("addr1");
+        let _addr2 = Addr::unchecked("addr2");
+        let info = mock_info(OWNER, &[]);
+
+        let not_acceptable_msg1 = InstantiateMsg {
+            threshold: Threshold::AbsoluteCount { weight: 10 },
+            locking_contract: Addr::unchecked("locking_contract"),
+            veto_threshold: None,
+        };
+
+        let not_acceptable_msg2 = InstantiateMsg {
+            threshold: Threshold::AbsolutePercentage {
+                percentage: Decimal::percent(50),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+            veto_threshold: None,
+        };
+
+        let expected_msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+            veto_threshold: None,
+        };
+
+        let res1 = instantiate(deps.as_mut(), mock_env(), info.clone(), not_acceptable_msg1);
+        assert_eq!(res1, Err(ContractError::AbsoluteCountNotAccepted {}));
+
+        let res2 = instantiate(deps.as_mut(), mock_env(), info.clone(), not_acceptable_msg2);
+        assert_eq!(res2, Err(ContractError::AbsolutePercentageNotAccepted {}));
+
+        let res3 = instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg);
+        assert_ne!(res3, Err(ContractError::AbsoluteCountNotAccepted {}));
+        assert_ne!(res3, Err(ContractError::AbsolutePercentageNotAccepted {}));
+        assert_ne!(res3, Err(ContractError::InvalidThreshold {}));
+        assert_ne!(res3, Err(ContractError::ZeroQuorumThreshold {}));
+        assert_ne!(res3, Err(ContractError::UnreachableQuorumThreshold {}));
+    }
+    // Propose Testcase
+    #[test]
+    fn test_propose() {
+        let mut deps = mock_dependencies1();
+        //let mut deps2=mock_dependencies1();
+        let info = mock_info(OWNER, &[]);
+        let msgs_com = vec![
+            ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 },
+            ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 34 },
+        ];
+
+        let propose_1 = Propose {
+            title: "propose".to_string(),
+            description: "test_propose".to_string(),
+            msgs: msgs_com,
+            // note: we ignore API-spec'd earliest if passed, always opens immediately
+            latest: Some(Expiration::Never {}),
+            app_id_param: 33,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+        };
+
+        //let msgs_length=msgs_com.len();
+        let k = execute_propose(deps.as_mut(), mock_env(), info.clone(), propose_1);
+        let msgs_2: Vec<ComdexMessages> = vec![];
+        let propose_2 = Propose {
+            title: "propose".to_string(),
+            description: "test_propose".to_string(),
+            msgs: msgs_2,
+            // note: we ignore API-spec'd earliest if passed, always opens immediately
+            latest: Some(Expiration::Never {}),
+            app_id_param: 33,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+        };
+        let f = execute_propose(deps.as_mut(), mock_env(), info, propose_2);
+    }
+
+    //Execute Testcase
+    #[test]
+    fn test_execute() {
+        let mut deps = mock_dependencies1();
+        let info = mock_info(OWNER, &[]);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        let a = Uint128::from(123u128);
+        let id = next_id(&mut deps.storage).unwrap();
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Passed,
+            duration: Duration::Time(40),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 14,
+            votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 3,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: id,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: true,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+
+        prop.update_status(&mock_env().block);
+
+        let _k = proposals().save(&mut deps.storage, id, &prop);
+
+        let err = execute_execute(deps.as_mut(), mock_env(), info, id);
+        assert_ne!(err, Err(ContractError::WrongExecuteStatus {}));
+        assert_ne!(err, Err(ContractError::NotExpiredYet {}));
+
+        assert_eq!(
+            err,
+            Ok(Response::new()
+                .add_messages(prop.msgs)
+                .add_attribute("action", "execute")
+                .add_attribute("sender", OWNER)
+                .add_attribute("proposal_id", id.to_string()))
+        );
+    }
+
+    //    Refund Testcase
+    #[test]
+    fn test_refund_works() {
+        // let id = next_id(&mut deps.storage).unwrap();
+        let mut deps = mock_dependencies1();
+        let deposit_amount = Uint128::from(10u128);
+        let info = mock_info(
+            OWNER,
+            &[Coin {
+                denom: "coin".to_string(),
+                amount: deposit_amount,
+            }],
+        );
+        let _v1 = Vote::Yes;
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_794_117);
+        let id = next_id(&mut deps.storage).unwrap();
+        let prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::Never {},
+            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Pending,
+            duration: Duration::Time(50000000),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 14,
+            votes: Votes {
+                yes: 10,
+                no: 5,
+                abstain: 10,
+                veto: 39,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: deposit_amount,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(33_u64),
+            current_deposit: 56,
+            app_mapping_id: id,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: false,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+        // if status is pending should get non passedProposalRefund error
+        let mut _k = proposals().save(&mut deps.storage, id, &prop);
+        let mut prop = proposals().load(&deps.storage, id).unwrap();
+        assert_eq!(prop.status, Status::Pending);
+        let g = execute_refund(deps.as_mut(), mock_env(), info.clone(), id).unwrap_err();
+
+        // If status is Rejected Should get Slashedpropsal Error
+        prop.status = Status::Rejected;
+        _k = proposals().save(&mut deps.storage, id, &prop);
+        let z = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+        match g {
+            ContractError::CustomError { .. } => {}
+            e => panic!("{:?}", e),
+        };
+
+        prop.status = Status::Passed;
+        _k = proposals().save(&mut deps.storage, id, &prop);
+        let mut prop = proposals().load(&deps.storage, id).unwrap();
+        assert_eq!(prop.status, Status::Passed);
+        let votes = prop.votes.clone();
+        assert_eq!(39, votes.veto);
+
+        prop.status = Status::Rejected;
+        prop.expires = Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_794_157));
+        let mut _prop = proposals().save(&mut deps.storage, id, &prop);
+        let mut prop = proposals().load(&deps.storage, id).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+        let i = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+        match g {
+            ContractError::CustomError { .. } => {}
+            e => panic!("{:?}", e),
+        };
+        assert_eq!(
+            (Decimal::percent(33) * Uint128::from(votes.total())).u128(),
+            21
+        );
+        let _votes = prop.votes.clone();
+
+        prop.status = Status::Passed;
+        prop.expires = Expiration::Never {};
+        _prop = proposals().save(&mut deps.storage, id, &prop);
+        pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter deposit");
+        let deposit_info = VOTERDEPOSIT
+            .may_load(&deps.storage, (id, &info.sender))
+            .unwrap();
+        assert_eq!(deposit_info, None);
+        let j = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+
+        prop.status = Status::Open;
+        let a = Uint128::from(123u128);
+        let deposit_info1 = Some(vec![Coin {
+            denom: "coin".to_string(),
+            amount: a,
+        }])
+        .unwrap();
+        let mut _vot = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &deposit_info1);
+        _vot = proposals().save(&mut deps.storage, id, &prop);
+        prop.status = Status::Passed;
+        _prop = proposals().save(&mut deps.storage, id, &prop);
+        let k = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+        match g {
+            ContractError::CustomError { .. } => {}
+            e => panic!("{:?}", e),
+        };
+    }
+
+    //   Deposit Testcase
+    #[test]
+    #[ignore = "Invalid"]
+    fn test_deposit() {
+        let mut deps = mock_dependencies1();
+        let a = Uint128::from(123u128);
+        let info = mock_info(
+            OWNER,
+            &[Coin {
+                denom: "coin".to_string(),
+                amount: a,
+            }],
+        );
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        let a = Uint128::from(123u128);
+        let id = next_id(&mut deps.storage).unwrap();
+
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Executed,
+            duration: Duration::Time(40),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 14,
+            votes: Votes {
+                yes: 10,
+                no: 5,
+                abstain: 10,
+                veto: 39,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: id,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: true,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+
+        prop.update_status(&mock_env().block);
+        let mut _prop = proposals().save(&mut deps.storage, id, &prop);
+        let mut _vote = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &info.funds);
+        let _deposit_info = VOTERDEPOSIT
+            .may_load(&deps.storage, (id, &info.sender))
+            .unwrap();
+        let err = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
+        assert_eq!(err, Err(ContractError::CannotDeposit {}));
+        prop.status = Status::Open;
+        _prop = proposals().save(&mut deps.storage, id, &prop);
+        let a = Uint128::from(123u128);
+        pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter deposit");
+        let deposit_info1 = Some(vec![Coin {
+            denom: "coin".to_string(),
+            amount: a,
+        }])
+        .unwrap();
+        let mut _deposit = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &deposit_info1);
+
+        prop.status = Status::Open;
+        prop.expires = Expiration::Never {};
+        _prop = proposals().save(&mut deps.storage, id, &prop);
+        //  If the status is not equal to open or pending, the error "CannotDeposit" will appear.
+        let err = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
+        assert_ne!(err, Err(ContractError::CannotDeposit {}));
+        assert_ne!(err, Err(ContractError::IncorrectDenomDeposit {}));
+
+        let z = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
+        assert_eq!(
+            z,
+            Ok(Response::new()
+                .add_attribute("action", "deposit")
+                .add_attribute("depositor", info.sender)
+                .add_attribute("proposal_id", id.to_string()))
+        );
+    }
+
+    //Vote Testcase
+    #[test]
+    fn test_vote() {
+        let mut deps = mock_dependencies1();
+        let a = Uint128::from(123u128);
+        let info = mock_info(OWNER, &[]);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_827_190);
+        let id = next_id(&mut deps.storage).unwrap();
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_897_190)),
+            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: id }],
+            status: Status::Passed,
+            duration: Duration::Time(400000000),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 40,
+            votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 3,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: id,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: false,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+
+        let mut _prop = proposals().save(&mut deps.storage, id, &prop);
+
+        let prop1 = proposals().load(&deps.storage, id).unwrap();
+        assert_eq!(prop1.current_status(&mock_env().block), Status::Passed);
+
+        // If the status is not equal to "open," an error message will appear.
+        let k = execute_vote(deps.as_mut(), mock_env(), info.clone(), id, Vote::Yes);
+        assert_eq!(k, Err(ContractError::NotOpen {}));
+        prop.status = Status::Open;
+        prop.expires = Expiration::Never {};
+        _prop = proposals().save(&mut deps.storage, id, &prop);
+        let prop1 = proposals().load(&deps.storage, id).unwrap();
+        assert_eq!(prop1.expires, Expiration::Never {});
+        let _m = execute_vote(deps.as_mut(), mock_env(), info, id, Vote::Yes);
+        assert_eq!(prop1.status, Status::Open);
+        assert_eq!(prop1.current_status(&mock_env().block), Status::Open);
+        assert!(!prop.expires.is_expired(&mock_env().block));
+        assert_eq!(prop1.current_status(&mock_env().block), Status::Open);
+        assert_eq!(prop.current_status(&mock_env().block), Status::Open);
+        assert_eq!(prop.status, Status::Open);
+    }
+
+    #[test]
+    fn test_slash() {
+        let mut deps = mock_dependencies1();
+        let info = mock_info(OWNER, &[]);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        let a = Uint128::from(123u128);
+        let id = next_id(&mut deps.storage).unwrap();
+
+        let expected_msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+            veto_threshold: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg).unwrap();
+        let mut prop = Proposal {
+            title: "prop".to_string(),
+            start_time: ts,
+            description: "test prop".to_string(),
+            start_height: 43,
+            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+            status: Status::Rejected,
+            duration: Duration::Time(40),
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            total_weight: 40,
+            votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 40,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: 33,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: false,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+        prop.expires = Expiration::Never {};
+        prop.update_status(&mock_env().block);
+
+        //If the majority of votes are vetoed, the Slash should be elected.
+        let _k = proposals().save(&mut deps.storage, id, &prop);
+        let res = execute_slash(deps.as_mut(), mock_env(), info.clone(), id);
+        assert_ne!(res, Err(ContractError::NotRejected {}));
+        assert_ne!(res, Err(ContractError::AlreadySlashed {}));
+        let prop1 = proposals().load(&deps.storage, id).unwrap();
+        // After running slash The is_slashed should be true.
+        assert_eq!(prop1.is_slashed, true);
+        assert_eq!(
+            res,
+            Ok(Response::new()
+                .add_message(ComdexMessages::MsgBurnGovTokensForApp {
+                    app_id: prop.app_mapping_id,
+                    amount: Coin {
+                        denom: "toVote".to_string(),
+                        amount: Uint128::from(56u128)
+                    },
+                    from: "cosmos2contract".to_string(),
+                })
+                .add_attribute("action", "Slash")
+                .add_attribute("trigger_address", info.sender)
+                .add_attribute("proposal_id", id.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_query() {
+        let mut deps = mock_dependencies1();
+        let info = mock_info(OWNER, &[]);
         let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
         let a = Uint128::from(123u128);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
         let id = next_id(&mut deps.storage).unwrap();
+
+        let expected_msg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+            veto_threshold: None,
+        };
+        instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg).unwrap();
         let mut prop = Proposal {
             title: "prop".to_string(),
             start_time: ts,
@@ -1168,787 +3142,2881 @@ mod tests {
                 threshold: Decimal::percent(50),
                 quorum: Decimal::percent(33),
             },
-            total_weight: 14,
-            votes: Votes {
-                yes: 32,
-                no: 24,
-                abstain: 10,
-                veto: 3,
+            total_weight: 14,
+            votes: Votes {
+                yes: 32,
+                no: 24,
+                abstain: 10,
+                veto: 3,
+            },
+            deposit: vec![Coin {
+                denom: "vote here".to_string(),
+                amount: a,
+            }],
+            proposer: "validator201".to_string(),
+            token_denom: "toVote".to_string(),
+            min_deposit: Uint128::from(45_u64),
+            current_deposit: 56,
+            app_mapping_id: 33,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: true,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+
+        prop.update_status(&mock_env().block);
+        let _k = proposals().save(&mut deps.storage, id, &prop);
+
+        // Threshold should be from ThreshouldQuorm
+        let res = query_threshold(deps.as_ref(), id);
+        assert_eq!(
+            res,
+            Ok(ThresholdResponse::ThresholdQuorum {
+                threshold: (Decimal::percent(50)),
+                quorum: (Decimal::percent(33)),
+                total_weight: (14)
+            })
+        );
+
+        // Query_propsal_detailed should give deatails respected to propsal id
+        let res = query_proposal_detailed(deps.as_ref(), mock_env(), id);
+        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        assert_eq!(
+            res,
+            Ok(msg::ProposalResponseTotal {
+                id,
+                title: "prop".to_string(),
+                start_time: ts,
+                description: "test prop".to_string(),
+                start_height: 43,
+                expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+                msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+                status: Status::Passed,
+                duration: Duration::Time(40),
+                threshold: Threshold::ThresholdQuorum {
+                    threshold: Decimal::percent(50),
+                    quorum: Decimal::percent(33),
+                },
+                veto_threshold: Decimal::percent(33),
+                total_weight: 14,
+                votes: Votes {
+                    yes: 32,
+                    no: 24,
+                    abstain: 10,
+                    veto: 3,
+                },
+                proposer: "validator201".to_string(),
+                token_denom: "toVote".to_string(),
+                current_deposit: 56,
+                refunded_deposit: 0,
+                slashed_deposit: 0,
+                end_time: None,
+                eta: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+                cosmos_msgs: vec![],
+                actions: vec![],
+                choices: vec![],
+                choice_votes: vec![],
+            })
+        );
+
+        // List Proposal Should give list of propsals
+        let res = list_proposals(deps.as_ref(), mock_env(), Option::None, Option::None);
+        assert_eq!(
+            res,
+            Ok(ProposalListResponse {
+                proposals: vec![ProposalResponse {
+                    id: id,
+                    title: "prop".to_string(),
+                    description: "test prop".to_string(),
+                    msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+                    status: Status::Passed,
+                    expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+                    threshold: ThresholdResponse::ThresholdQuorum {
+                        threshold: Decimal::percent(50),
+                        quorum: Decimal::percent(33),
+                        total_weight: 14,
+                    }
+                }]
+            })
+        );
+
+        let res = reverse_proposals(deps.as_ref(), mock_env(), Option::None, Option::None);
+        assert_eq!(
+            res,
+            Ok(ProposalListResponse {
+                proposals: vec![ProposalResponse {
+                    id: id,
+                    title: "prop".to_string(),
+                    description: "test prop".to_string(),
+                    msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
+                    status: Status::Passed,
+                    expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
+                    threshold: ThresholdResponse::ThresholdQuorum {
+                        threshold: Decimal::percent(50),
+                        quorum: Decimal::percent(33),
+                        total_weight: 14,
+                    }
+                }]
+            })
+        );
+
+        let cfg = Config {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking_contract"),
+            guardian: None,
+            admin_app_id: None,
+            veto_threshold: Decimal::percent(33),
+        };
+        _ = CONFIG.save(&mut deps.storage, &cfg);
+
+        let ballot = Ballot {
+            weight: 10,
+            vote: Vote::Yes,
+            delegated_weight: 0,
+            choice: None,
+            options: vec![],
+        };
+        _ = BALLOTS.save(&mut deps.storage, (id, &info.sender), &ballot);
+
+        // Query Vote should show the Vote respected to voter and Propasal id.
+        let res = query_vote(deps.as_ref(), id, OWNER.to_string());
+        assert_eq!(
+            res,
+            Ok(VoteResponse {
+                vote: Some(VoteInfo {
+                    proposal_id: 1,
+                    voter: "admin0001".to_string(),
+                    vote: Vote::Yes,
+                    weight: 10,
+                    options: vec![],
+                })
+            })
+        );
+
+        // List Votes should show the VoteInfo respected to proposal id.
+        let res = list_votes(deps.as_ref(), id, Option::None, Option::None);
+        assert_eq!(
+            res,
+            Ok(VoteListResponse {
+                votes: vec![VoteInfo {
+                    proposal_id: 1,
+                    voter: "admin0001".to_string(),
+                    vote: Vote::Yes,
+                    weight: 10,
+                    options: vec![],
+                }]
+            })
+        );
+    }
+
+    #[allow(non_snake_case)]
+    #[test]
+    fn test_deposit_non_gov_token() {
+        // Mock deps
+        let mut env = mock_env();
+        let mut deps = mock_dependencies1();
+
+        let DENOM: &str = "DNM";
+        env.block.time = Timestamp::from_seconds(1000);
+        env.block.height = 10;
+
+        // Instantiate
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::one(),
+                quorum: Decimal::one(),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
+        };
+        let sender = Addr::unchecked("sender");
+        let info = mock_info(sender.as_str(), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+
+        // Save a proposal
+        let proposal = Proposal {
+            title: "title".to_string(),
+            start_time: Timestamp::from_seconds(1200),
+            description: "description".to_string(),
+            start_height: 10,
+            expires: Expiration::AtHeight(20),
+            msgs: vec![],
+            status: Status::Pending,
+            duration: Duration::Height(10),
+            threshold: imsg.threshold,
+            total_weight: 0,
+            votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            deposit: vec![],
+            proposer: sender.to_string(),
+            token_denom: DENOM.to_string(),
+            min_deposit: Uint128::from(100_u64),
+            current_deposit: 0,
+            app_mapping_id: 1,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: false,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+
+        // Deposit gov token
+        let info = mock_info(sender.as_str(), &coins(100, DENOM));
+        let result = execute_deposit(deps.as_mut(), env.clone(), info.clone(), 1).unwrap();
+        assert_eq!(result.messages.len(), 0);
+
+        // Check correct update in VOTERDEPOSIT
+        let sender_deposits = VOTERDEPOSIT
+            .load(deps.as_ref().storage, (1, &sender))
+            .unwrap();
+        assert_eq!(sender_deposits.len(), 1);
+        assert_eq!(sender_deposits[0].amount.u128(), 100);
+        assert_eq!(sender_deposits[0].denom, DENOM.to_string());
+
+        // Check correct update in PROPOSALS
+        let proposal_data = proposals().load(deps.as_ref().storage, 1).unwrap();
+        match proposal_data.status {
+            Status::Pending => {}
+            e => panic!("{:?}", e),
+        };
+        assert_eq!(proposal_data.current_deposit, 100u128);
+        assert_eq!(proposal_data.deposit.len(), 1);
+        assert_eq!(proposal_data.deposit[0].amount.u128(), 100u128);
+        assert_eq!(proposal_data.deposit[0].denom, DENOM.to_string());
+
+        // Deposit non-gov token
+        let info = mock_info(sender.as_str(), &coins(100, "denom2"));
+        let result = execute_deposit(deps.as_mut(), env.clone(), info, 1).unwrap_err();
+
+        // Check no update in PROPOSALS
+        let proposal_data = proposals().load(deps.as_ref().storage, 1).unwrap();
+        assert_eq!(proposal_data.current_deposit, 100);
+        assert_eq!(proposal_data.deposit.len(), 1);
+    }
+
+    #[test]
+    fn test_refund_invalid_requests() {
+        // Mock deps
+        let mut env = mock_env();
+        let mut deps = mock_dependencies1();
+        let sender = Addr::unchecked("sender");
+        let denom: &str = "denom1";
+
+        env.block.time = Timestamp::from_seconds(1000);
+        env.block.height = 10;
+
+        // Instantiate
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::one(),
+                quorum: Decimal::one(),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
+        };
+        let sender = Addr::unchecked("sender");
+        let info = mock_info(sender.as_str(), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+
+        // Save a proposal
+        let mut proposal = Proposal {
+            title: "title".to_string(),
+            start_time: Timestamp::from_seconds(1200),
+            description: "description".to_string(),
+            start_height: 10,
+            expires: Expiration::AtHeight(20),
+            msgs: vec![],
+            status: Status::Pending,
+            duration: Duration::Height(10),
+            threshold: imsg.threshold,
+            total_weight: 0,
+            votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            deposit: vec![],
+            proposer: sender.to_string(),
+            token_denom: denom.to_string(),
+            min_deposit: Uint128::from(100_u64),
+            current_deposit: 0,
+            app_mapping_id: 1,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: false,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+
+        // Execute refund on a pending proposal
+        let info = mock_info(sender.as_str(), &[]);
+
+        let result = execute_refund(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
+        match result {
+            ContractError::PendingProposal {} => {}
+            e => panic!("{:?}", e),
+        };
+
+        // Execute refund on an open proposal
+        proposal.status = Status::Open;
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+        let result = execute_refund(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
+        match result {
+            ContractError::OpenProposal {} => {}
+            e => panic!("{:?}", e),
+        };
+    }
+
+    #[test]
+    fn test_slash_invalid_requests() {
+        let mut env = mock_env();
+        let mut deps = mock_dependencies1();
+        env.block.time = Timestamp::from_seconds(100);
+        env.block.height = 10;
+        let denom: &str = "denom1";
+
+        // Instantiate
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::one(),
+                quorum: Decimal::one(),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
+        };
+        let sender = Addr::unchecked("sender");
+        let info = mock_info(sender.as_str(), &[]);
+        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
+
+        // Save a proposal
+        let proposal = Proposal {
+            title: "title".to_string(),
+            start_time: Timestamp::from_seconds(1200),
+            description: "description".to_string(),
+            start_height: 10,
+            expires: Expiration::AtHeight(20),
+            msgs: vec![],
+            status: Status::Open,
+            duration: Duration::Height(10),
+            threshold: imsg.threshold,
+            total_weight: 0,
+            votes: Votes {
+                yes: 0,
+                no: 0,
+                abstain: 0,
+                veto: 0,
+            },
+            deposit: vec![],
+            proposer: sender.to_string(),
+            token_denom: denom.to_string(),
+            min_deposit: Uint128::from(100_u64),
+            current_deposit: 0,
+            app_mapping_id: 1,
+            veto_threshold: Decimal::percent(33),
+            is_slashed: false,
+            refunded_deposit: 0,
+            slashed_deposit: 0,
+            slashable_deposit: 0,
+            voter_reward: 0,
+            end_time: None,
+            cosmos_msgs: vec![],
+            actions: vec![],
+            choices: vec![],
+            choice_votes: vec![],
+            params: AppGovParams::default(),
+        };
+
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+
+        // Slash for open proposal
+        let info = mock_info(sender.as_str(), &[]);
+
+        let result = execute_slash(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
+        match result {
+            ContractError::NotRejected {} => {}
+            e => panic!("{:?}", e),
+        };
+
+        // Slash for rejected proposal but not vetoed
+        env.block.time = Timestamp::from_seconds(5000);
+        env.block.height = 30;
+        let info = mock_info(sender.as_str(), &[]);
+
+        let result = execute_slash(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
+        match result {
+            ContractError::ProposalNotVetoed {} => {}
+            e => panic!("{:?}", e),
+        };
+    }
+
+    #[test]
+    fn test_delegated_voting() {
+        let mut deps = mock_app_dependencies();
+        let env = mock_env();
+
+        let proposal = test_proposal();
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+
+        // delegating to yourself is not allowed
+        let err = execute_delegate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            "delegate".to_string(),
+            1,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::SelfDelegation {});
+
+        for delegator in ["delegator1", "delegator2"] {
+            execute_delegate(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(delegator, &[]),
+                "delegate".to_string(),
+                1,
+            )
+            .unwrap();
+        }
+        let res = query_delegation(deps.as_ref(), "delegator1".to_string(), 1).unwrap();
+        assert_eq!(res.delegate, Some(Addr::unchecked("delegate")));
+
+        let res = query_delegated_power(deps.as_ref(), "delegate".to_string(), 1, None).unwrap();
+        assert_eq!(res.delegators, 2);
+        assert_eq!(res.power, 50);
+
+        // the delegate votes with its own and the delegated power
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            1,
+            Vote::Yes,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.votes.yes, 60);
+        let ballot = BALLOTS
+            .load(&deps.storage, (1, &Addr::unchecked("delegate")))
+            .unwrap();
+        assert_eq!(ballot.weight, 60);
+        assert_eq!(ballot.delegated_weight, 50);
+
+        // a delegator voting directly overrides the delegate
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            1,
+            Vote::No,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.votes.yes, 30);
+        assert_eq!(prop.votes.no, 30);
+        let ballot = BALLOTS
+            .load(&deps.storage, (1, &Addr::unchecked("delegate")))
+            .unwrap();
+        assert_eq!(ballot.weight, 30);
+        assert_eq!(ballot.delegated_weight, 20);
+
+        execute_undelegate(deps.as_mut(), env.clone(), mock_info("delegator1", &[]), 1).unwrap();
+        let err =
+            execute_undelegate(deps.as_mut(), env, mock_info("delegator1", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::NoDelegation {});
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut deps = mock_dependencies1();
+        let env = mock_env();
+        let proposer = Addr::unchecked("proposer");
+        let depositor = Addr::unchecked("depositor");
+
+        let proposal = Proposal {
+            votes: Votes::yes(10),
+            proposer: proposer.to_string(),
+            current_deposit: 150,
+            ..test_proposal()
+        };
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+        let ballot = Ballot {
+            weight: 10,
+            vote: Vote::Yes,
+            delegated_weight: 0,
+            choice: None,
+            options: vec![],
+        };
+        BALLOTS
+            .save(deps.as_mut().storage, (1, &proposer), &ballot)
+            .unwrap();
+        VOTERDEPOSIT
+            .save(deps.as_mut().storage, (1, &proposer), &coins(100, "denom1"))
+            .unwrap();
+        VOTERDEPOSIT
+            .save(deps.as_mut().storage, (1, &depositor), &coins(50, "denom1"))
+            .unwrap();
+
+        // only the proposer can cancel
+        let err =
+            execute_cancel(deps.as_mut(), env.clone(), mock_info("depositor", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // not once somebody else voted
+        BALLOTS
+            .save(deps.as_mut().storage, (1, &depositor), &ballot)
+            .unwrap();
+        let err =
+            execute_cancel(deps.as_mut(), env.clone(), mock_info("proposer", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::CannotCancel {});
+
+        BALLOTS.remove(deps.as_mut().storage, (1, &depositor));
+        let res =
+            execute_cancel(deps.as_mut(), env.clone(), mock_info("proposer", &[]), 1).unwrap();
+        assert_eq!(
+            res.messages
+                .into_iter()
+                .map(|m| m.msg)
+                .collect::<Vec<CosmosMsg<ComdexMessages>>>(),
+            vec![
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: depositor.to_string(),
+                    amount: coins(50, "denom1"),
+                }),
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: proposer.to_string(),
+                    amount: coins(100, "denom1"),
+                }),
+            ]
+        );
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.current_status(&env.block), Status::Cancelled);
+        assert!(VOTERDEPOSIT
+            .may_load(&deps.storage, (1, &proposer))
+            .unwrap()
+            .is_none());
+
+        // a cancelled proposal stays cancelled
+        let err = execute_cancel(deps.as_mut(), env, mock_info("proposer", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::CannotCancel {});
+    }
+
+    #[test]
+    fn test_execute_timelock() {
+        let mut deps = mock_dependencies1();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1000);
+
+        let mut proposal = Proposal {
+            start_time: Timestamp::from_seconds(500),
+            start_height: 10,
+            expires: Expiration::AtTime(Timestamp::from_seconds(900)),
+            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 }],
+            duration: Duration::Time(400),
+            votes: Votes::yes(60),
+            params: AppGovParams {
+                execution_delay: 200,
+                execution_grace_period: Some(50),
+                ..AppGovParams::default()
+            },
+            ..test_proposal()
+        };
+        assert_eq!(
+            proposal.eta(),
+            Expiration::AtTime(Timestamp::from_seconds(1100))
+        );
+        proposal.update_status(&env.block);
+        assert_eq!(proposal.status, Status::Passed);
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+
+        // still within the execution delay
+        let err =
+            execute_execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::TimelockNotExpired {});
+
+        // after the grace period
+        env.block.time = Timestamp::from_seconds(1150);
+        let err =
+            execute_execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::ExecutionWindowExpired {});
+
+        env.block.time = Timestamp::from_seconds(1120);
+        let res = execute_execute(deps.as_mut(), env, mock_info(OWNER, &[]), 1).unwrap();
+        assert_eq!(res.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_guardian_veto() {
+        let mut deps = mock_dependencies1();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1000);
+
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            imsg.clone(),
+        )
+        .unwrap();
+        let mut cfg = CONFIG.load(&deps.storage).unwrap();
+        cfg.guardian = Some(Addr::unchecked("guardian"));
+        CONFIG.save(deps.as_mut().storage, &cfg).unwrap();
+
+        let proposal = Proposal {
+            start_time: Timestamp::from_seconds(500),
+            start_height: 10,
+            expires: Expiration::AtTime(Timestamp::from_seconds(900)),
+            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 }],
+            duration: Duration::Time(400),
+            votes: Votes::yes(60),
+            ..test_proposal()
+        };
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+
+        let err = execute_guardian_veto(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), 1)
+            .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        let res = execute_guardian_veto(deps.as_mut(), env.clone(), mock_info("guardian", &[]), 1)
+            .unwrap();
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "proposal_vetoed");
+
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.current_status(&env.block), Status::Vetoed);
+
+        // a vetoed proposal can neither be executed nor vetoed again
+        let err =
+            execute_execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::WrongExecuteStatus {});
+        let err =
+            execute_guardian_veto(deps.as_mut(), env, mock_info("guardian", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::NotPassed {});
+    }
+
+    #[test]
+    fn test_app_veto_threshold() {
+        let mut deps = mock_app_dependencies();
+        let env = mock_env();
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        APPGOVPARAMS
+            .save(
+                deps.as_mut().storage,
+                1,
+                &AppGovParams {
+                    quorum: Some(Decimal::percent(20)),
+                    veto_threshold: Some(Decimal::percent(50)),
+                    ..AppGovParams::default()
+                },
+            )
+            .unwrap();
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+
+        let expired = |deps: &OwnedDeps<_, _, _, ComdexQuery>, id: u64| Proposal {
+            expires: Expiration::AtHeight(env.block.height),
+            votes: Votes {
+                yes: 20,
+                no: 0,
+                abstain: 0,
+                veto: 15,
+            },
+            ..proposals().load(&deps.storage, id).unwrap()
+        };
+        let default_veto = expired(&deps, 1);
+        let app_veto = expired(&deps, 2);
+        assert_eq!(
+            app_veto.threshold,
+            Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(20),
+            }
+        );
+        assert_eq!(app_veto.veto_threshold, Decimal::percent(50));
+
+        // 43% veto is above the default veto threshold
+        assert!(default_veto.check_vetoed(&env.block));
+
+        // but not above the one of the app
+        assert!(!app_veto.check_vetoed(&env.block));
+        assert_eq!(app_veto.current_status(&env.block), Status::Passed);
+    }
+
+    #[test]
+    fn test_proposal_actions() {
+        let mut deps = mock_dependencies1();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1000);
+
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            imsg.clone(),
+        )
+        .unwrap();
+
+        let new_threshold = Threshold::ThresholdQuorum {
+            threshold: Decimal::percent(60),
+            quorum: Decimal::percent(40),
+        };
+        let app_params = AppGovParams {
+            max_voting_period: Some(3600),
+            ..AppGovParams::default()
+        };
+        let mut proposal = Proposal {
+            start_time: Timestamp::from_seconds(500),
+            start_height: 10,
+            expires: Expiration::AtTime(Timestamp::from_seconds(900)),
+            duration: Duration::Time(400),
+            votes: Votes::yes(60),
+            actions: vec![
+                ProposalAction::UpdateThreshold {
+                    threshold: new_threshold.clone(),
+                    veto_threshold: Some(Decimal::percent(40)),
+                },
+                ProposalAction::UpdateLockingContract {
+                    address: Addr::unchecked("new-locking-contract"),
+                },
+                ProposalAction::UpdateAppGovParams {
+                    params: Box::new(app_params.clone()),
+                },
+                ProposalAction::UpdateGuardian {
+                    guardian: Some(Addr::unchecked("guardian")),
+                },
+            ],
+            ..test_proposal()
+        };
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+
+        // settings shared by all apps change through the admin app only
+        let err =
+            execute_execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::GlobalActionNotAllowed {});
+        let deps_mut = deps.as_mut();
+        apply_action(
+            deps_mut.storage,
+            deps_mut.api,
+            0,
+            ProposalAction::UpdateAdminApp { app_id: Some(1) },
+        )
+        .unwrap();
+
+        // a proposal carrying only actions can be executed
+        let res = execute_execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), 1).unwrap();
+        assert_eq!(res.messages.len(), 0);
+
+        let cfg = CONFIG.load(&deps.storage).unwrap();
+        assert_eq!(cfg.threshold, new_threshold);
+        assert_eq!(cfg.veto_threshold, Decimal::percent(40));
+        assert_eq!(
+            cfg.locking_contract,
+            Addr::unchecked("new-locking-contract")
+        );
+        assert_eq!(cfg.guardian, Some(Addr::unchecked("guardian")));
+        assert_eq!(APPGOVPARAMS.load(&deps.storage, 1).unwrap(), app_params);
+
+        // invalid actions fail the execution
+        proposal.actions = vec![ProposalAction::UpdateThreshold {
+            threshold: Threshold::AbsoluteCount { weight: 10 },
+            veto_threshold: None,
+        }];
+        proposals()
+            .save(deps.as_mut().storage, 2, &proposal)
+            .unwrap();
+        let err = execute_execute(deps.as_mut(), env, mock_info(OWNER, &[]), 2).unwrap_err();
+        assert_eq!(err, ContractError::AbsoluteCountNotAccepted {});
+    }
+
+    #[test]
+    fn test_proposal_cosmos_msgs() {
+        let mut deps = mock_dependencies1();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1000);
+
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            imsg.clone(),
+        )
+        .unwrap();
+
+        let execute_msg = ProposalMsg::WasmExecute {
+            contract_addr: "fee-collector".to_string(),
+            msg: Binary::from(b"{}".to_vec()),
+            funds: vec![],
+        };
+        let err = validate_proposal_msg(deps.as_ref(), &execute_msg, 1).unwrap_err();
+        assert_eq!(err, ContractError::ContractNotAllowed {});
+
+        let err = validate_proposal_msg(
+            deps.as_ref(),
+            &ProposalMsg::BankSend {
+                to_address: "receiver".to_string(),
+                amount: coins(0, "ucmdx"),
             },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: id,
-            is_slashed: true,
+            1,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidTreasurySpend {});
+
+        let deps_mut = deps.as_mut();
+        apply_action(
+            deps_mut.storage,
+            deps_mut.api,
+            1,
+            ProposalAction::UpdateAllowedContracts {
+                add: vec![Addr::unchecked("fee-collector")],
+                remove: vec![],
+            },
+        )
+        .unwrap();
+        validate_proposal_msg(deps.as_ref(), &execute_msg, 1).unwrap();
+        // allowed for app 1 only
+        let err = validate_proposal_msg(deps.as_ref(), &execute_msg, 2).unwrap_err();
+        assert_eq!(err, ContractError::ContractNotAllowed {});
+
+        let proposal = Proposal {
+            start_time: Timestamp::from_seconds(500),
+            start_height: 10,
+            expires: Expiration::AtTime(Timestamp::from_seconds(900)),
+            duration: Duration::Time(400),
+            votes: Votes::yes(60),
+            cosmos_msgs: vec![
+                ProposalMsg::BankSend {
+                    to_address: "receiver".to_string(),
+                    amount: coins(10, "ucmdx"),
+                },
+                execute_msg,
+            ],
+            ..test_proposal()
         };
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+        proposals()
+            .save(deps.as_mut().storage, 2, &proposal)
+            .unwrap();
+        fund_treasury(deps.as_mut().storage, 1, &coin(20, "ucmdx")).unwrap();
+        deps.querier
+            .update_balance("cosmos2contract", coins(20, "ucmdx"));
 
-        prop.update_status(&mock_env().block);
+        let res = execute_execute(deps.as_mut(), env.clone(), mock_info(OWNER, &[]), 1).unwrap();
+        assert_eq!(res.messages.len(), 2);
+        assert_eq!(
+            query_treasury(deps.as_ref(), 1).unwrap().funds,
+            coins(10, "ucmdx")
+        );
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "receiver".to_string(),
+                amount: coins(10, "ucmdx"),
+            })
+        );
 
-        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+        // contracts removed from the allow list cannot be called anymore
+        let deps_mut = deps.as_mut();
+        apply_action(
+            deps_mut.storage,
+            deps_mut.api,
+            1,
+            ProposalAction::UpdateAllowedContracts {
+                add: vec![],
+                remove: vec![Addr::unchecked("fee-collector")],
+            },
+        )
+        .unwrap();
+        let err = execute_execute(deps.as_mut(), env, mock_info(OWNER, &[]), 2).unwrap_err();
+        assert_eq!(err, ContractError::ContractNotAllowed {});
+    }
 
-        let err = execute_execute(deps.as_mut(), mock_env(), info, id);
-        assert_ne!(err, Err(ContractError::WrongExecuteStatus {}));
-        assert_ne!(err, Err(ContractError::NotExpiredYet {}));
+    #[test]
+    fn test_multiple_choice() {
+        let mut deps = mock_dependencies1();
+        deps.querier.update_wasm(mock_locking_query);
+        let mut env = mock_env();
+
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            imsg.clone(),
+        )
+        .unwrap();
+
+        let mut proposal = Proposal {
+            expires: Expiration::AtTime(env.block.time.plus_seconds(100)),
+            ..test_proposal()
+        };
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+        proposal.choices = vec![
+            msg::ProposalChoice {
+                title: "low fee".to_string(),
+                msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 }],
+            },
+            msg::ProposalChoice {
+                title: "high fee".to_string(),
+                msgs: vec![ComdexMessages::MsgWhitelistAppIdLiquidation { app_id: 1 }],
+            },
+        ];
+        proposal.choice_votes = vec![0, 0];
+        proposals()
+            .save(deps.as_mut().storage, 2, &proposal)
+            .unwrap();
 
+        let err = execute_vote_choice(deps.as_mut(), env.clone(), mock_info("delegate", &[]), 1, 0)
+            .unwrap_err();
+        assert_eq!(err, ContractError::NotMultipleChoice {});
+        let err = execute_vote_choice(deps.as_mut(), env.clone(), mock_info("delegate", &[]), 2, 2)
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidChoice {});
+        let err = execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            2,
+            Vote::Yes,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::ChoiceRequired {});
+
+        execute_vote_choice(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &[]),
+            2,
+            1,
+        )
+        .unwrap();
+        execute_vote_choice(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            2,
+            0,
+        )
+        .unwrap();
+        execute_vote_choice(deps.as_mut(), env.clone(), mock_info("delegate", &[]), 2, 1).unwrap();
+        let prop = proposals().load(&deps.storage, 2).unwrap();
+        assert_eq!(prop.choice_votes, vec![30, 30]);
+        assert_eq!(prop.votes.yes, 60);
+        // a tie has no winner
+        assert_eq!(prop.winning_choice(), None);
+
+        // changing to a plain vote takes the weight off the choice
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            2,
+            Vote::No,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 2).unwrap();
+        assert_eq!(prop.choice_votes, vec![30, 20]);
+        assert_eq!(prop.winning_choice(), Some(0));
+
+        env.block.time = env.block.time.plus_seconds(200);
+        assert_eq!(prop.current_status(&env.block), Status::Passed);
+        let res = execute_execute(deps.as_mut(), env, mock_info(OWNER, &[]), 2).unwrap();
+        assert_eq!(res.messages.len(), 1);
         assert_eq!(
-            err,
-            Ok(Response::new()
-                .add_messages(prop.msgs)
-                .add_attribute("action", "execute")
-                .add_attribute("sender", OWNER)
-                .add_attribute("proposal_id", id.to_string()))
+            res.messages[0].msg,
+            CosmosMsg::Custom(ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 1 })
         );
     }
 
-    //    Refund Testcase
     #[test]
-    fn test_refund_works() {
-        // let id = next_id(&mut deps.storage).unwrap();
-        let mut deps = mock_dependencies1();
-        let deposit_amount = Uint128::from(10u128);
-        let info = mock_info(
-            OWNER,
-            &[Coin {
-                denom: "coin".to_string(),
-                amount: deposit_amount,
-            }],
+    fn test_weighted_vote() {
+        let mut deps = mock_app_dependencies();
+        let env = mock_env();
+
+        let proposal = test_proposal();
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+
+        let err = execute_vote_weighted(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            1,
+            vec![(Vote::Yes, Decimal::percent(50))],
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidVoteOptions {});
+
+        execute_vote_weighted(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            1,
+            vec![
+                (Vote::Yes, Decimal::percent(50)),
+                (Vote::No, Decimal::percent(30)),
+                (Vote::Abstain, Decimal::percent(20)),
+            ],
+        )
+        .unwrap();
+        let res = query_vote(deps.as_ref(), 1, "delegator2".to_string()).unwrap();
+        let vote = res.vote.unwrap();
+        assert_eq!(vote.vote, Vote::Yes);
+        assert_eq!(vote.weight, 30);
+        assert_eq!(
+            vote.options,
+            vec![(Vote::Yes, 15), (Vote::No, 9), (Vote::Abstain, 6)]
         );
-        let _v1 = Vote::Yes;
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_794_117);
-        let id = next_id(&mut deps.storage).unwrap();
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::Never {},
-            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Pending,
-            duration: Duration::Time(50000000),
+
+        // the delegated power is split like the delegate's own
+        execute_delegate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &[]),
+            "delegate".to_string(),
+            1,
+        )
+        .unwrap();
+        execute_vote_weighted(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            1,
+            vec![
+                (Vote::Yes, Decimal::percent(70)),
+                (Vote::Veto, Decimal::percent(30)),
+            ],
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(
+            prop.votes,
+            Votes {
+                yes: 36,
+                no: 9,
+                abstain: 6,
+                veto: 9,
+            }
+        );
+
+        // a direct vote of the delegator takes its share off every option of the delegate
+        execute_vote(
+            deps.as_mut(),
+            env,
+            mock_info("delegator1", &[]),
+            1,
+            Vote::No,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(
+            prop.votes,
+            Votes {
+                yes: 22,
+                no: 29,
+                abstain: 6,
+                veto: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn test_vote_change_and_audit() {
+        let mut deps = mock_dependencies1();
+        deps.querier.update_wasm(mock_locking_query);
+        let env = mock_env();
+
+        let imsg = InstantiateMsg {
             threshold: Threshold::ThresholdQuorum {
                 threshold: Decimal::percent(50),
                 quorum: Decimal::percent(33),
             },
-            total_weight: 14,
-            votes: Votes {
-                yes: 10,
-                no: 5,
-                abstain: 10,
-                veto: 39,
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
+        };
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            imsg.clone(),
+        )
+        .unwrap();
+
+        // ballot recorded with more power than the locking contract reports now
+        let proposal = Proposal {
+            votes: Votes::yes(25),
+            ..test_proposal()
+        };
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+        let ballot = Ballot {
+            weight: 25,
+            vote: Vote::Yes,
+            delegated_weight: 0,
+            choice: None,
+            options: vec![],
+        };
+        BALLOTS
+            .save(
+                deps.as_mut().storage,
+                (1, &Addr::unchecked("delegator1")),
+                &ballot,
+            )
+            .unwrap();
+
+        // the stored ballot is taken back, not the current power of 20
+        execute_vote(
+            deps.as_mut(),
+            env,
+            mock_info("delegator1", &[]),
+            1,
+            Vote::No,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.votes.yes, 0);
+        assert_eq!(prop.votes.no, 20);
+
+        let audit = query_vote_audit(deps.as_ref(), 1).unwrap();
+        assert_eq!(audit.ballots, 1);
+        assert!(audit.matches);
+
+        // a drifted tally is reported
+        let mut prop = proposals().load(&deps.storage, 1).unwrap();
+        prop.votes.yes = 5;
+        proposals().save(deps.as_mut().storage, 1, &prop).unwrap();
+        let audit = query_vote_audit(deps.as_ref(), 1).unwrap();
+        assert!(!audit.matches);
+        assert_eq!(audit.stored.yes, 5);
+        assert_eq!(audit.computed.yes, 0);
+    }
+
+    #[test]
+    fn test_revoting_policy() {
+        let mut deps = mock_dependencies1();
+        deps.querier.update_wasm(mock_locking_query);
+        let mut env = mock_env();
+
+        let imsg = InstantiateMsg {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
             },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: deposit_amount,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(33_u64),
-            current_deposit: 56,
-            app_mapping_id: id,
-            is_slashed: false,
+            locking_contract: Addr::unchecked("locking-contract"),
+            veto_threshold: None,
         };
-        // if status is pending should get non passedProposalRefund error
-        let mut _k = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop.status, Status::Pending);
-        let g = execute_refund(deps.as_mut(), mock_env(), info.clone(), id).unwrap_err();
+        instantiate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(OWNER, &[]),
+            imsg.clone(),
+        )
+        .unwrap();
 
-        // If status is Rejected Should get Slashedpropsal Error
-        prop.status = Status::Rejected;
-        _k = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let z = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
-        match g {
-            ContractError::CustomError { .. } => {}
-            e => panic!("{:?}", e),
+        let mut proposal = Proposal {
+            expires: Expiration::AtTime(env.block.time.plus_seconds(100)),
+            params: AppGovParams {
+                allow_revoting: Some(false),
+                ..AppGovParams::default()
+            },
+            ..test_proposal()
+        };
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
+        proposal.params = AppGovParams {
+            vote_lock_period: Some(20),
+            ..AppGovParams::default()
         };
+        proposals()
+            .save(deps.as_mut().storage, 2, &proposal)
+            .unwrap();
 
-        prop.status = Status::Passed;
-        _k = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop.status, Status::Passed);
-        let votes = prop.votes.clone();
-        assert_eq!(39, votes.veto);
+        let voter = mock_info("delegator1", &[]);
+        execute_vote(deps.as_mut(), env.clone(), voter.clone(), 1, Vote::Yes).unwrap();
+        let err = execute_vote(deps.as_mut(), env.clone(), voter.clone(), 1, Vote::No).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyVoted {});
+
+        // ballots can change until the final window
+        execute_vote(deps.as_mut(), env.clone(), voter.clone(), 2, Vote::Yes).unwrap();
+        execute_vote(deps.as_mut(), env.clone(), voter.clone(), 2, Vote::No).unwrap();
+        env.block.time = env.block.time.plus_seconds(80);
+        let err =
+            execute_vote(deps.as_mut(), env.clone(), voter.clone(), 2, Vote::Yes).unwrap_err();
+        assert_eq!(err, ContractError::VoteLocked {});
+        // first votes are still accepted
+        execute_vote(
+            deps.as_mut(),
+            env,
+            mock_info("delegator2", &[]),
+            2,
+            Vote::Yes,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_propose_spam_protection() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        APPGOVPARAMS
+            .save(
+                deps.as_mut().storage,
+                1,
+                &AppGovParams {
+                    min_proposer_power: Some(Uint128::from(20_u64)),
+                    min_initial_deposit: Some(Decimal::percent(25)),
+                    max_open_proposals: Some(1),
+                    ..AppGovParams::default()
+                },
+            )
+            .unwrap();
+
+        let err = execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &coins(100, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientProposerPower {});
+
+        let err = execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(10, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InsufficientInitialDeposit {});
+
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        let err = execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::TooManyOpenProposals {});
+
+        // once the first one is closed a new one can be opened
+        env.block.time = env.block.time.plus_seconds(200);
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        let open_proposals =
+            open_proposals_of(&deps.storage, &env.block, 1, &Addr::unchecked("delegator1"))
+                .unwrap();
+        assert_eq!(open_proposals, 1);
+    }
+
+    #[test]
+    fn test_deposit_period() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        let mut params = AppGovParams {
+            max_deposit_period: Some(50),
+            ..AppGovParams::default()
+        };
+        APPGOVPARAMS
+            .save(deps.as_mut().storage, 1, &params)
+            .unwrap();
+
+        // proposal 1 stays Pending and fails once the deposit period is over
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        // proposal 2 opens later and gets the full voting period
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+
+        env.block.time = env.block.time.plus_seconds(40);
+        execute_deposit(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &coins(80, "ugov")),
+            2,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 2).unwrap();
+        assert_eq!(prop.status, Status::Open);
+        assert_eq!(prop.start_time, env.block.time);
+        assert_eq!(
+            prop.expires,
+            Expiration::AtTime(env.block.time.plus_seconds(100))
+        );
+
+        env.block.time = env.block.time.plus_seconds(10);
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.current_status(&env.block), Status::DepositFailed);
+        let err = execute_deposit(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &coins(80, "ugov")),
+            1,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::CannotDeposit {});
+
+        // deposits are refunded by default
+        let err =
+            execute_slash(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::NotRejected {});
+        let res =
+            execute_refund(deps.as_mut(), env.clone(), mock_info("delegator1", &[]), 1).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "delegator1".to_string(),
+                amount: coins(30, "ugov"),
+            })
+        );
+
+        // or burnt if the app is configured so
+        params.burn_failed_deposits = true;
+        APPGOVPARAMS
+            .save(deps.as_mut().storage, 1, &params)
+            .unwrap();
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(50);
+        let err = execute_refund(deps.as_mut(), env.clone(), mock_info("delegator1", &[]), 3)
+            .unwrap_err();
+        assert_eq!(err, ContractError::SlashedProposal {});
+        let res = execute_slash(deps.as_mut(), env, mock_info("anyone", &[]), 3).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Custom(ComdexMessages::MsgBurnGovTokensForApp {
+                app_id: 1,
+                amount: Coin {
+                    amount: Uint128::from(30_u64),
+                    denom: "ugov".to_string(),
+                },
+                from: "cosmos2contract".to_string(),
+            })
+        );
+        let prop = proposals().load(&deps.storage, 3).unwrap();
+        assert_eq!((prop.current_deposit, prop.slashed_deposit), (0, 30));
+    }
+
+    #[test]
+    fn test_refund_all() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        APPGOVPARAMS
+            .save(
+                deps.as_mut().storage,
+                1,
+                &AppGovParams {
+                    max_deposit_period: Some(50),
+                    ..AppGovParams::default()
+                },
+            )
+            .unwrap();
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        for depositor in ["delegate", "delegator2"].iter() {
+            execute_deposit(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(depositor, &coins(10, "ugov")),
+                1,
+            )
+            .unwrap();
+        }
+
+        let err = execute_refund_all(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            1,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::PendingProposal {});
+
+        env.block.time = env.block.time.plus_seconds(50);
+        let res = execute_refund_all(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            1,
+            Some(2),
+        )
+        .unwrap();
+        let refunded: Vec<_> = res
+            .messages
+            .iter()
+            .map(|msg| match &msg.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, .. }) => to_address.clone(),
+                _ => panic!("unexpected message"),
+            })
+            .collect();
+        assert_eq!(refunded, vec!["delegate", "delegator1"]);
+        assert_eq!(
+            REFUNDCURSOR.load(&deps.storage, 1).unwrap(),
+            Addr::unchecked("delegator1")
+        );
 
-        prop.status = Status::Rejected;
-        prop.expires = Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_794_157));
-        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let mut prop = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop.status, Status::Rejected);
-        let i = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
-        match g {
-            ContractError::CustomError { .. } => {}
-            e => panic!("{:?}", e),
-        };
+        // the next call picks up where the last one stopped
+        let res = execute_refund_all(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            1,
+            Some(2),
+        )
+        .unwrap();
         assert_eq!(
-            (Decimal::percent(33) * Uint128::from(votes.total())).u128(),
-            21
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "delegator2".to_string(),
+                amount: coins(10, "ugov"),
+            })
         );
-        let _votes = prop.votes.clone();
+        assert_eq!(res.messages.len(), 1);
+        assert!(REFUNDCURSOR.may_load(&deps.storage, 1).unwrap().is_none());
 
-        prop.status = Status::Passed;
-        prop.expires = Expiration::Never {};
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter deposit");
-        let deposit_info = VOTERDEPOSIT
-            .may_load(&deps.storage, (id, &info.sender))
+        let err = execute_refund(deps.as_mut(), env, mock_info("delegator1", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::NoDeposit {});
+    }
+
+    #[test]
+    fn test_deposit_accounting() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        APPGOVPARAMS
+            .save(
+                deps.as_mut().storage,
+                1,
+                &AppGovParams {
+                    max_deposit_period: Some(50),
+                    ..AppGovParams::default()
+                },
+            )
             .unwrap();
-        assert_eq!(deposit_info, None);
-        let j = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        for depositor in ["delegate", "delegate", "delegator2"].iter() {
+            execute_deposit(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(depositor, &coins(10, "ugov")),
+                1,
+            )
+            .unwrap();
+        }
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.deposit, coins(60, "ugov"));
+        assert_eq!(prop.current_deposit, 60);
 
-        prop.status = Status::Open;
-        let a = Uint128::from(123u128);
-        let deposit_info1 = Some(vec![Coin {
-            denom: "coin".to_string(),
-            amount: a,
-        }])
+        let res = query_deposit(deps.as_ref(), 1, "delegate".to_string()).unwrap();
+        assert_eq!(
+            res.deposit,
+            Some(DepositInfo {
+                proposal_id: 1,
+                depositor: "delegate".to_string(),
+                amount: coins(20, "ugov"),
+                slashed: vec![],
+            })
+        );
+        let res = list_deposits(deps.as_ref(), 1, Some("delegate".to_string()), Some(1)).unwrap();
+        assert_eq!(res.deposits.len(), 1);
+        assert_eq!(res.deposits[0].depositor, "delegator1");
+        assert_eq!(res.deposits[0].amount, coins(30, "ugov"));
+
+        // refunds are taken off the amount held by the contract
+        env.block.time = env.block.time.plus_seconds(50);
+        execute_refund(deps.as_mut(), env.clone(), mock_info("delegate", &[]), 1).unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!((prop.current_deposit, prop.refunded_deposit), (40, 20));
+        let res = query_deposit(deps.as_ref(), 1, "delegate".to_string()).unwrap();
+        assert_eq!(res.deposit, None);
+
+        // deposits of a slashed proposal report the slashed part apart
+        APPGOVPARAMS
+            .save(
+                deps.as_mut().storage,
+                1,
+                &AppGovParams {
+                    max_deposit_period: Some(50),
+                    burn_failed_deposits: true,
+                    slash_fraction: Some(Decimal::percent(40)),
+                    ..AppGovParams::default()
+                },
+            )
+            .unwrap();
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
         .unwrap();
-        let mut _vot = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &deposit_info1);
-        _vot = PROPOSALS.save(&mut deps.storage, id, &prop);
-        prop.status = Status::Passed;
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let k = execute_refund(deps.as_mut(), mock_env(), info.clone(), id);
-        match g {
-            ContractError::CustomError { .. } => {}
-            e => panic!("{:?}", e),
-        };
+        execute_deposit(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &coins(15, "ugov")),
+            2,
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(50);
+        execute_slash(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 2).unwrap();
+        let res = list_deposits(deps.as_ref(), 2, None, None).unwrap();
+        assert_eq!(
+            res.deposits,
+            vec![
+                DepositInfo {
+                    proposal_id: 2,
+                    depositor: "delegator1".to_string(),
+                    amount: coins(18, "ugov"),
+                    slashed: coins(12, "ugov"),
+                },
+                DepositInfo {
+                    proposal_id: 2,
+                    depositor: "delegator2".to_string(),
+                    amount: coins(9, "ugov"),
+                    slashed: coins(6, "ugov"),
+                },
+            ]
+        );
+        execute_refund(deps.as_mut(), env, mock_info("delegator2", &[]), 2).unwrap();
+        let res = query_deposit(deps.as_ref(), 2, "delegator2".to_string()).unwrap();
+        assert_eq!(res.deposit, None);
+        let res = query_deposit(deps.as_ref(), 2, "delegator1".to_string()).unwrap();
+        assert_eq!(res.deposit.unwrap().slashed, coins(12, "ugov"));
     }
 
-    //   Deposit Testcase
     #[test]
-    #[ignore = "Invalid"]
-    fn test_deposit() {
-        let mut deps = mock_dependencies1();
-        let a = Uint128::from(123u128);
-        let info = mock_info(
-            OWNER,
-            &[Coin {
-                denom: "coin".to_string(),
-                amount: a,
-            }],
-        );
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
-        let a = Uint128::from(123u128);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
-
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Executed,
-            duration: Duration::Time(40),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 14,
-            votes: Votes {
-                yes: 10,
-                no: 5,
-                abstain: 10,
-                veto: 39,
+    fn test_partial_slash() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        let mut params = AppGovParams {
+            slash_fraction: Some(Decimal::percent(40)),
+            slash_destination: SlashDestination::CommunityPool {
+                address: Addr::unchecked("pool"),
             },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: id,
-            is_slashed: true,
+            slash_on_quorum_failure: true,
+            ..AppGovParams::default()
         };
-
-        prop.update_status(&mock_env().block);
-        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let mut _vote = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &info.funds);
-        let _deposit_info = VOTERDEPOSIT
-            .may_load(&deps.storage, (id, &info.sender))
+        APPGOVPARAMS
+            .save(deps.as_mut().storage, 1, &params)
             .unwrap();
-        let err = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
-        assert_eq!(err, Err(ContractError::CannotDeposit {}));
-        prop.status = Status::Open;
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let a = Uint128::from(123u128);
-        pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter deposit");
-        let deposit_info1 = Some(vec![Coin {
-            denom: "coin".to_string(),
-            amount: a,
-        }])
+
+        // only the proposer votes, so the quorum is not reached
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            app_proposal(vec![]),
+        )
         .unwrap();
-        let mut _deposit = VOTERDEPOSIT.save(&mut deps.storage, (id, &info.sender), &deposit_info1);
+        env.block.time = env.block.time.plus_seconds(100);
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.current_status(&env.block), Status::Rejected);
+        assert!(prop.quorum_failed());
 
-        prop.status = Status::Open;
-        prop.expires = Expiration::Never {};
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        //  If the status is not equal to open or pending, the error "CannotDeposit" will appear.
-        let err = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
-        assert_ne!(err, Err(ContractError::CannotDeposit {}));
-        assert_ne!(err, Err(ContractError::IncorrectDenomDeposit {}));
+        let res =
+            execute_refund(deps.as_mut(), env.clone(), mock_info("delegator1", &[]), 1).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "delegator1".to_string(),
+                amount: coins(66, "ugov"),
+            })
+        );
+        let res = execute_slash(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "pool".to_string(),
+                amount: coins(44, "ugov"),
+            })
+        );
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(
+            (
+                prop.current_deposit,
+                prop.refunded_deposit,
+                prop.slashed_deposit
+            ),
+            (0, 66, 44)
+        );
 
-        let z = execute_deposit(deps.as_mut(), mock_env(), info.clone(), id);
+        // vetoed proposal, the veto voters claim the slashed share for their own power
+        params.slash_destination = SlashDestination::NoVetoVoters;
+        APPGOVPARAMS
+            .save(deps.as_mut().storage, 1, &params)
+            .unwrap();
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        execute_delegate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            "delegate".to_string(),
+            1,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            2,
+            Vote::Veto,
+        )
+        .unwrap();
+        env.block.time = env.block.time.plus_seconds(100);
+        let res = execute_slash(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 2).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(proposals().load(&deps.storage, 2).unwrap().voter_reward, 44);
+        for (voter, share) in [("delegate", 11), ("delegator2", 33)].iter() {
+            let res = execute_claim_slashed(deps.as_mut(), mock_info(voter, &[]), 2).unwrap();
+            assert_eq!(
+                res.messages[0].msg,
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: voter.to_string(),
+                    amount: coins(*share, "ugov"),
+                })
+            );
+        }
+        let err = execute_claim_slashed(deps.as_mut(), mock_info("delegate", &[]), 2).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyClaimed {});
+        let err =
+            execute_claim_slashed(deps.as_mut(), mock_info("delegator1", &[]), 2).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+        let err = execute_claim_slashed(deps.as_mut(), mock_info("delegate", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+        let res = execute_refund(deps.as_mut(), env, mock_info("delegator1", &[]), 2).unwrap();
         assert_eq!(
-            z,
-            Ok(Response::new()
-                .add_attribute("action", "deposit")
-                .add_attribute("depositor", info.sender)
-                .add_attribute("proposal_id", id.to_string()))
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "delegator1".to_string(),
+                amount: coins(66, "ugov"),
+            })
         );
     }
 
-    //Vote Testcase
     #[test]
-    fn test_vote() {
-        let mut deps = mock_dependencies1();
-        let a = Uint128::from(123u128);
-        let info = mock_info(OWNER, &[]);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_827_190);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_897_190)),
-            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: id }],
-            status: Status::Passed,
-            duration: Duration::Time(400000000),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 40,
-            votes: Votes {
-                yes: 32,
-                no: 24,
-                abstain: 10,
-                veto: 3,
-            },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: id,
-            is_slashed: false,
-        };
-
-        let mut _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
+    fn test_finalize() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        // proposal 1 misses the quorum, proposal 2 passes
+        for proposer in ["delegator1", "delegator2"].iter() {
+            execute_propose(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(proposer, &coins(110, "ugov")),
+                app_proposal(vec![]),
+            )
+            .unwrap();
+        }
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            2,
+            Vote::Yes,
+        )
+        .unwrap();
 
-        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop1.current_status(&mock_env().block), Status::Passed);
+        let err =
+            execute_finalize(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::VotingNotEnded {});
 
-        // If the status is not equal to "open," an error message will appear.
-        let k = execute_vote(deps.as_mut(), mock_env(), info.clone(), id, Vote::Yes);
-        assert_eq!(k, Err(ContractError::NotOpen {}));
-        prop.status = Status::Open;
-        prop.expires = Expiration::Never {};
-        _prop = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
-        assert_eq!(prop1.expires, Expiration::Never {});
-        let _m = execute_vote(deps.as_mut(), mock_env(), info, id, Vote::Yes);
-        assert_eq!(prop1.status, Status::Open);
-        assert_eq!(prop1.current_status(&mock_env().block), Status::Open);
-        assert!(!prop.expires.is_expired(&mock_env().block));
-        assert_eq!(prop1.current_status(&mock_env().block), Status::Open);
-        assert_eq!(prop.current_status(&mock_env().block), Status::Open);
-        assert_eq!(prop.status, Status::Open);
+        env.block.time = env.block.time.plus_seconds(100);
+        let res =
+            execute_finalize(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap();
+        assert_eq!(res.events[0].ty, "proposal_rejected");
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.status, Status::Rejected);
+        assert_eq!(prop.end_time, Some(env.block.time));
+        let err =
+            execute_finalize(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyFinalized {});
+
+        // open and already finalized proposals are skipped
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        let res = execute_finalize_expired(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("anyone", &[]),
+            1,
+            Some(1),
+        )
+        .unwrap();
+        assert_eq!(res.events.len(), 1);
+        assert_eq!(res.events[0].ty, "proposal_passed");
+        assert_eq!(
+            res.events[0].attributes[1..3],
+            [
+                cosmwasm_std::attr("app_id", "1"),
+                cosmwasm_std::attr("yes", "40")
+            ]
+        );
+        assert_eq!(
+            proposals().load(&deps.storage, 2).unwrap().status,
+            Status::Passed
+        );
+        assert_eq!(
+            proposals().load(&deps.storage, 3).unwrap().status,
+            Status::Open
+        );
+        // only the proposals left in Pending or Open are read
+        let res =
+            execute_finalize_expired(deps.as_mut(), env, mock_info("anyone", &[]), 1, Some(1))
+                .unwrap();
+        assert!(res.events.is_empty());
     }
 
     #[test]
-    fn test_slash() {
-        let mut deps = mock_dependencies1();
-        let info = mock_info(OWNER, &[]);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
-        let a = Uint128::from(123u128);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
+    fn test_close() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
 
-        let expected_msg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
-        instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg).unwrap();
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Rejected,
-            duration: Duration::Time(40),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 40,
-            votes: Votes {
-                yes: 32,
-                no: 24,
-                abstain: 10,
-                veto: 40,
-            },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: 33,
-            is_slashed: false,
+        // cw3 tooling encodes the same message
+        let msg = cw3::Cw3Contract(Addr::unchecked("gov")).close(1).unwrap();
+        let msg = match msg {
+            CosmosMsg::Wasm(cosmwasm_std::WasmMsg::Execute { msg, .. }) => msg,
+            _ => panic!("unexpected message"),
         };
-        prop.expires = Expiration::Never {};
-        prop.update_status(&mock_env().block);
+        assert_eq!(
+            from_binary::<ExecuteMsg>(&msg).unwrap(),
+            ExecuteMsg::Close { proposal_id: 1 }
+        );
+
+        // proposal 1 misses the quorum, proposal 2 passes
+        for proposer in ["delegator1", "delegator2"].iter() {
+            execute_propose(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(proposer, &coins(110, "ugov")),
+                app_proposal(vec![]),
+            )
+            .unwrap();
+        }
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            2,
+            Vote::Yes,
+        )
+        .unwrap();
 
-        //If the majority of votes are vetoed, the Slash should be elected.
-        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
-        let res = execute_slash(deps.as_mut(), mock_env(), info.clone(), id);
-        assert_ne!(res, Err(ContractError::NotRejected {}));
-        assert_ne!(res, Err(ContractError::AlreadySlashed {}));
-        let prop1 = PROPOSALS.load(&deps.storage, id).unwrap();
-        // After running slash The is_slashed should be true.
-        assert_eq!(prop1.is_slashed, true);
+        let err =
+            execute_close(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::VotingNotEnded {});
+
+        env.block.time = env.block.time.plus_seconds(100);
+        let err =
+            execute_close(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 2).unwrap_err();
+        assert_eq!(err, ContractError::WrongCloseStatus {});
+
+        let res = execute_close(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap();
+        assert_eq!(res.events[0].ty, "proposal_rejected");
         assert_eq!(
-            res,
-            Ok(Response::new()
-                .add_message(ComdexMessages::MsgBurnGovTokensForApp {
-                    app_id: prop.app_mapping_id,
-                    amount: Coin {
-                        denom: "toVote".to_string(),
-                        amount: Uint128::from(56u128)
-                    },
-                    from: "cosmos2contract".to_string(),
-                })
-                .add_attribute("action", "Slash")
-                .add_attribute("trigger_address", info.sender)
-                .add_attribute("proposal_id", id.to_string()))
+            proposals().load(&deps.storage, 1).unwrap().status,
+            Status::Rejected
         );
+        let err =
+            execute_close(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::AlreadyFinalized {});
+
+        execute_refund(deps.as_mut(), env, mock_info("delegator1", &[]), 1).unwrap();
     }
 
     #[test]
-    fn test_query() {
-        let mut deps = mock_dependencies1();
-        let info = mock_info(OWNER, &[]);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
-        let a = Uint128::from(123u128);
-        pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-        let id = next_id(&mut deps.storage).unwrap();
+    fn test_migrate_proposal_indexes() {
+        let mut app_deps = mock_app_dependencies();
+        for proposer in ["delegator1", "delegator2"].iter() {
+            execute_propose(
+                app_deps.as_mut(),
+                mock_env(),
+                mock_info(proposer, &coins(110, "ugov")),
+                app_proposal(vec![]),
+            )
+            .unwrap();
+        }
 
-        let expected_msg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
-        instantiate(deps.as_mut(), mock_env(), info.clone(), expected_msg).unwrap();
-        let mut prop = Proposal {
-            title: "prop".to_string(),
-            start_time: ts,
-            description: "test prop".to_string(),
-            start_height: 43,
-            expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-            msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-            status: Status::Passed,
-            duration: Duration::Time(40),
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            total_weight: 14,
-            votes: Votes {
-                yes: 32,
-                no: 24,
-                abstain: 10,
-                veto: 3,
+        // state as written before the indexes existed
+        let mut deps = cosmwasm_std::testing::mock_dependencies();
+        let legacy_proposals: Map<u64, Proposal> = Map::new("proposals");
+        for id in 1..=2 {
+            let prop = proposals().load(&app_deps.storage, id).unwrap();
+            legacy_proposals.save(&mut deps.storage, id, &prop).unwrap();
+        }
+        let ballot = BALLOTS
+            .load(&app_deps.storage, (2, &Addr::unchecked("delegator2")))
+            .unwrap();
+        BALLOTS
+            .save(
+                &mut deps.storage,
+                (2, &Addr::unchecked("delegator2")),
+                &ballot,
+            )
+            .unwrap();
+        PROPOSALSBYAPP
+            .save(&mut deps.storage, 1, &vec![1, 2])
+            .unwrap();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+        migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                refund_address: Addr::unchecked("refund"),
             },
-            deposit: vec![Coin {
-                denom: "vote here".to_string(),
-                amount: a,
-            }],
-            proposer: "validator201".to_string(),
-            token_denom: "toVote".to_string(),
-            min_deposit: Uint128::from(45_u64),
-            current_deposit: 56,
-            app_mapping_id: 33,
-            is_slashed: true,
-        };
+        )
+        .unwrap();
 
-        prop.update_status(&mock_env().block);
-        let _k = PROPOSALS.save(&mut deps.storage, id, &prop);
+        let by_app = proposals()
+            .idx
+            .app
+            .prefix(1)
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(by_app, vec![1, 2]);
+        let open = proposals()
+            .idx
+            .status
+            .prefix((1, Status::Open as u8))
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .count();
+        assert_eq!(open, 2);
+        let by_proposer = proposals()
+            .idx
+            .app_proposer
+            .prefix((1, "delegator2".to_string()))
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(by_proposer, vec![2]);
+        assert!(VOTERBALLOTS.has(&deps.storage, (&Addr::unchecked("delegator2"), 1, 2)));
+        assert!(PROPOSALSBYAPP.may_load(&deps.storage, 1).unwrap().is_none());
+    }
 
-        // Threshold should be from ThreshouldQuorm
-        let res = query_threshold(deps.as_ref(), id);
-        assert_eq!(
-            res,
-            Ok(ThresholdResponse::ThresholdQuorum {
-                threshold: (Decimal::percent(50)),
-                quorum: (Decimal::percent(33)),
-                total_weight: (14)
-            })
+    #[test]
+    fn test_migrate_legacy_proposal() {
+        let mut deps = cosmwasm_std::testing::mock_dependencies();
+
+        // a proposal and a ballot as stored before any of the defaulted fields existed
+        let key = Map::<u64, Proposal>::new("proposals").key(1);
+        deps.storage.set(
+            &key,
+            br#"{
+                "title":"title","start_time":"1571797419879305533","description":"description",
+                "start_height":12345,"expires":{"at_time":"1571797519879305533"},"msgs":[],
+                "status":"open","duration":{"time":100},
+                "threshold":{"threshold_quorum":{"threshold":"0.5","quorum":"0.33"}},
+                "total_weight":"100","votes":{"yes":"10","no":"0","abstain":"0","veto":"0"},
+                "deposit":[{"denom":"ugov","amount":"110"}],"proposer":"delegator1",
+                "token_denom":"ugov","min_deposit":"100","current_deposit":"110",
+                "app_mapping_id":1,"is_slashed":false
+            }"#,
         );
+        let key = BALLOTS.key((1, &Addr::unchecked("delegator1")));
+        deps.storage.set(&key, br#"{"weight":"10","vote":"yes"}"#);
+        VOTERDEPOSIT
+            .save(
+                &mut deps.storage,
+                (1, &Addr::unchecked("delegator1")),
+                &coins(110, "ugov"),
+            )
+            .unwrap();
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
 
-        // Query_propsal_detailed should give deatails respected to propsal id
-        let res = query_proposal_detailed(deps.as_ref(), mock_env(), id);
-        let ts = cosmwasm_std::Timestamp::from_nanos(1_655_745_339);
+        migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                refund_address: Addr::unchecked("refund"),
+            },
+        )
+        .unwrap();
+
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.params, AppGovParams::default());
+        assert!(prop.choices.is_empty() && prop.choice_votes.is_empty());
+        assert_eq!(prop.refunded_deposit, 0);
+        assert_eq!(prop.slashable_deposit, 110);
+        assert_eq!(prop.end_time, None);
         assert_eq!(
-            res,
-            Ok(msg::ProposalResponseTotal {
-                id,
-                title: "prop".to_string(),
-                start_time: ts,
-                description: "test prop".to_string(),
-                start_height: 43,
-                expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-                msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-                status: Status::Passed,
-                duration: Duration::Time(40),
-                threshold: Threshold::ThresholdQuorum {
-                    threshold: Decimal::percent(50),
-                    quorum: Decimal::percent(33),
-                },
-                total_weight: 14,
-                votes: Votes {
-                    yes: 32,
-                    no: 24,
-                    abstain: 10,
-                    veto: 3,
-                },
-                proposer: "validator201".to_string(),
-                token_denom: "toVote".to_string(),
-                current_deposit: 56
-            })
+            prop.veto_threshold,
+            Decimal::percent(DEFAULT_VETO_THRESHOLD)
         );
-
-        // List Proposal Should give list of propsals
-        let res = list_proposals(deps.as_ref(), mock_env(), Option::None, Option::None);
+        let by_app = proposals()
+            .idx
+            .app
+            .prefix(1)
+            .keys(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(by_app, vec![1]);
         assert_eq!(
-            res,
-            Ok(ProposalListResponse {
-                proposals: vec![ProposalResponse {
-                    id: id,
-                    title: "prop".to_string(),
-                    description: "test prop".to_string(),
-                    msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-                    status: Status::Passed,
-                    expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-                    threshold: ThresholdResponse::ThresholdQuorum {
-                        threshold: Decimal::percent(50),
-                        quorum: Decimal::percent(33),
-                        total_weight: 14,
-                    }
-                }]
-            })
+            ESCROWEDDEPOSITS.load(&deps.storage, "ugov").unwrap(),
+            Uint128::from(110_u64)
         );
 
-        let res = reverse_proposals(deps.as_ref(), mock_env(), Option::None, Option::None);
+        let ballot = BALLOTS
+            .load(&deps.storage, (1, &Addr::unchecked("delegator1")))
+            .unwrap();
+        assert_eq!(ballot.delegated_weight, 0);
+        assert!(ballot.options.is_empty());
+        assert!(VOTERBALLOTS.has(&deps.storage, (&Addr::unchecked("delegator1"), 1, 1)));
+    }
+
+    #[test]
+    fn test_migrate_legacy_escrow() {
+        let mut deps = cosmwasm_std::testing::mock_dependencies();
+
+        // open, refunded to delegator1 only, and slashed proposals as stored before,
+        // none of them took refunds off `current_deposit`
+        for (id, status, current_deposit, is_slashed) in [
+            (1, "open", 110, false),
+            (2, "rejected", 200, false),
+            (3, "rejected", 150, true),
+        ] {
+            let key = Map::<u64, Proposal>::new("proposals").key(id);
+            let value = format!(
+                r#"{{
+                    "title":"title","start_time":"1571797419879305533","description":"description",
+                    "start_height":12345,"expires":{{"at_time":"1571797519879305533"}},"msgs":[],
+                    "status":"{}","duration":{{"time":100}},
+                    "threshold":{{"threshold_quorum":{{"threshold":"0.5","quorum":"0.33"}}}},
+                    "total_weight":"100","votes":{{"yes":"0","no":"0","abstain":"0","veto":"0"}},
+                    "deposit":[],"proposer":"delegator1","token_denom":"ugov","min_deposit":"100",
+                    "current_deposit":"{}","app_mapping_id":1,"is_slashed":{}
+                }}"#,
+                status, current_deposit, is_slashed
+            );
+            deps.storage.set(&key, value.as_bytes());
+        }
+        for (id, depositor, amount) in [
+            (1, "delegator1", 110),
+            (2, "delegator2", 100),
+            (3, "delegator1", 150),
+        ] {
+            VOTERDEPOSIT
+                .save(
+                    &mut deps.storage,
+                    (id, &Addr::unchecked(depositor)),
+                    &coins(amount, "ugov"),
+                )
+                .unwrap();
+        }
+        set_contract_version(&mut deps.storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+        migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                refund_address: Addr::unchecked("refund"),
+            },
+        )
+        .unwrap();
+
+        // the deposits of proposal 1 and what is left of proposal 2
         assert_eq!(
-            res,
-            Ok(ProposalListResponse {
-                proposals: vec![ProposalResponse {
-                    id: id,
-                    title: "prop".to_string(),
-                    description: "test prop".to_string(),
-                    msgs: vec![ComdexMessages::MsgWhitelistAppIdVaultInterest { app_id: 33 }],
-                    status: Status::Passed,
-                    expires: Expiration::AtTime(cosmwasm_std::Timestamp::from_nanos(1_655_745_430)),
-                    threshold: ThresholdResponse::ThresholdQuorum {
-                        threshold: Decimal::percent(50),
-                        quorum: Decimal::percent(33),
-                        total_weight: 14,
-                    }
-                }]
-            })
+            ESCROWEDDEPOSITS.load(&deps.storage, "ugov").unwrap(),
+            Uint128::from(210_u64)
         );
+    }
 
-        let cfg = Config {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::percent(50),
-                quorum: Decimal::percent(33),
-            },
-            locking_contract: Addr::unchecked("locking_contract"),
-        };
-        _ = CONFIG.save(&mut deps.storage, &cfg);
+    #[test]
+    fn test_list_app_proposals() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        let start = env.block.time;
+        for proposer in ["delegator1", "delegator2", "delegator1"].iter() {
+            execute_propose(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(proposer, &coins(110, "ugov")),
+                app_proposal(vec![]),
+            )
+            .unwrap();
+            env.block.time = env.block.time.plus_seconds(10);
+        }
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            2,
+            Vote::Yes,
+        )
+        .unwrap();
 
-        let ballot = Ballot {
-            weight: 10,
-            vote: Vote::Yes,
+        let list = |deps: Deps<ComdexQuery>, env: Env, query: AppProposalQuery| {
+            let res = get_proposals_by_app(deps, env, 1, query).unwrap();
+            assert_eq!(res.proposal_count, 3);
+            res.proposals.iter().map(|p| p.id).collect::<Vec<_>>()
+        };
+        let query = || AppProposalQuery {
+            start_after: None,
+            start_before: None,
+            limit: None,
+            ascending: false,
+            status: None,
+            proposer: None,
+            start_time_from: None,
+            start_time_to: None,
         };
-        _ = BALLOTS.save(&mut deps.storage, (id, &info.sender), &ballot);
 
-        // Query Vote should show the Vote respected to voter and Propasal id.
-        let res = query_vote(deps.as_ref(), id, OWNER.to_string());
+        // newest first, paged with the id of the last one
+        let ids = list(
+            deps.as_ref(),
+            env.clone(),
+            AppProposalQuery {
+                limit: Some(2),
+                ..query()
+            },
+        );
+        assert_eq!(ids, vec![3, 2]);
+        let res = get_proposals_by_app(
+            deps.as_ref(),
+            env.clone(),
+            1,
+            AppProposalQuery {
+                limit: Some(2),
+                ..query()
+            },
+        )
+        .unwrap();
+        assert_eq!(res.next, Some(2));
+        let res = get_proposals_by_app(
+            deps.as_ref(),
+            env.clone(),
+            1,
+            AppProposalQuery {
+                start_before: Some(2),
+                ..query()
+            },
+        )
+        .unwrap();
         assert_eq!(
-            res,
-            Ok(VoteResponse {
-                vote: Some(VoteInfo {
-                    proposal_id: 1,
-                    voter: "admin0001".to_string(),
-                    vote: Vote::Yes,
-                    weight: 10
-                })
-            })
+            res.proposals.iter().map(|p| p.id).collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(res.next, None);
+        let ids = list(
+            deps.as_ref(),
+            env.clone(),
+            AppProposalQuery {
+                start_after: Some(1),
+                ascending: true,
+                ..query()
+            },
+        );
+        assert_eq!(ids, vec![2, 3]);
+
+        let ids = list(
+            deps.as_ref(),
+            env.clone(),
+            AppProposalQuery {
+                proposer: Some("delegator1".to_string()),
+                ..query()
+            },
+        );
+        assert_eq!(ids, vec![3, 1]);
+        let ids = list(
+            deps.as_ref(),
+            env.clone(),
+            AppProposalQuery {
+                start_time_from: Some(start.plus_seconds(10)),
+                start_time_to: Some(start.plus_seconds(15)),
+                ..query()
+            },
+        );
+        assert_eq!(ids, vec![2]);
+
+        // the status is the current one, not the stored one
+        env.block.time = start.plus_seconds(200);
+        let ids = list(
+            deps.as_ref(),
+            env.clone(),
+            AppProposalQuery {
+                status: Some(Status::Rejected),
+                ..query()
+            },
+        );
+        assert_eq!(ids, vec![3, 1]);
+
+        // stored and current statuses are merged in id order
+        let mut prop = proposals().load(&deps.storage, 1).unwrap();
+        let old = prop.clone();
+        prop.status = Status::Rejected;
+        proposals()
+            .replace(deps.as_mut().storage, 1, Some(&prop), Some(&old))
+            .unwrap();
+        let ids = list(
+            deps.as_ref(),
+            env.clone(),
+            AppProposalQuery {
+                status: Some(Status::Rejected),
+                ascending: true,
+                ..query()
+            },
+        );
+        assert_eq!(ids, vec![1, 3]);
+        let ids = list(
+            deps.as_ref(),
+            env,
+            AppProposalQuery {
+                status: Some(Status::Executed),
+                ..query()
+            },
         );
+        assert!(ids.is_empty());
+    }
+
+    #[test]
+    fn test_proposals_by_proposer_and_votes_by_voter() {
+        let mut deps = mock_app_dependencies();
+        let env = mock_env();
+        for proposer in ["delegator1", "delegator2", "delegator1"].iter() {
+            execute_propose(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(proposer, &coins(110, "ugov")),
+                app_proposal(vec![]),
+            )
+            .unwrap();
+        }
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            1,
+            Vote::No,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            3,
+            Vote::Veto,
+        )
+        .unwrap();
+
+        let res = list_proposals_by_proposer(
+            deps.as_ref(),
+            env.clone(),
+            "delegator1".to_string(),
+            None,
+            None,
+        )
+        .unwrap();
+        let ids: Vec<_> = res.proposals.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![1, 3]);
+        let res =
+            list_proposals_by_proposer(deps.as_ref(), env, "delegator1".to_string(), Some(1), None)
+                .unwrap();
+        assert_eq!(res.proposals.len(), 1);
+
+        // the proposer's own ballot is listed too
+        let res =
+            list_votes_by_voter(deps.as_ref(), "delegator2".to_string(), 1, None, None).unwrap();
+        let votes: Vec<_> = res.votes.iter().map(|v| (v.proposal_id, v.vote)).collect();
+        assert_eq!(votes, vec![(1, Vote::No), (2, Vote::Yes), (3, Vote::Veto)]);
+        let res = list_votes_by_voter(deps.as_ref(), "delegator2".to_string(), 1, Some(1), Some(1))
+            .unwrap();
+        assert_eq!(res.votes[0].proposal_id, 2);
+        assert_eq!(res.votes[0].weight, 30);
+        let res =
+            list_votes_by_voter(deps.as_ref(), "delegator2".to_string(), 2, None, None).unwrap();
+        assert!(res.votes.is_empty());
+    }
+
+    #[test]
+    fn test_locking_queries() {
+        let deps = mock_app_dependencies();
+        let env = mock_env();
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Supply {
+                denom: "ugov".to_string(),
+            },
+        )
+        .unwrap();
+        let supply: TokenSupply = from_binary(&res).unwrap();
+        assert_eq!(supply.vtoken, 100);
+
+        let res = query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::TotalVTokens {
+                address: Addr::unchecked("delegator2"),
+                denom: "ugov".to_string(),
+                height: None,
+            },
+        )
+        .unwrap();
+        let power: Uint128 = from_binary(&res).unwrap();
+        assert_eq!(power, Uint128::from(30_u64));
+
+        // invalid addresses are an error, not a panic
+        query(
+            deps.as_ref(),
+            env,
+            QueryMsg::TotalVTokens {
+                address: Addr::unchecked(""),
+                denom: "ugov".to_string(),
+                height: None,
+            },
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn test_governance_contract_helper() {
+        let gov = GovernanceContract::new(Addr::unchecked("governance"));
+
+        let msg = gov.deposit(4, coins(50, "ugov")).unwrap();
+        match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }) => {
+                assert_eq!(contract_addr, "governance");
+                assert_eq!(funds, coins(50, "ugov"));
+                assert_eq!(
+                    from_binary::<ExecuteMsg>(&msg).unwrap(),
+                    ExecuteMsg::Deposit { proposal_id: 4 }
+                );
+            }
+            _ => panic!("expected a wasm execute"),
+        }
+
+        let msg = gov.vote(4, Vote::Yes).unwrap();
+        match msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, funds, .. }) => {
+                assert!(funds.is_empty());
+                assert_eq!(
+                    from_binary::<ExecuteMsg>(&msg).unwrap(),
+                    ExecuteMsg::Vote {
+                        proposal_id: 4,
+                        vote: Vote::Yes
+                    }
+                );
+            }
+            _ => panic!("expected a wasm execute"),
+        }
+
+        // serve the config of an instantiated contract to the typed query
+        let deps = mock_app_dependencies();
+        let config = CONFIG.load(&deps.storage).unwrap();
+        let served = to_binary(&config).unwrap();
+        let mut querier = MockQuerier::<ComdexQuery>::new(&[]);
+        querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } => {
+                assert_eq!(contract_addr, "governance");
+                match from_binary(msg).unwrap() {
+                    QueryMsg::Config {} => SystemResult::Ok(ContractResult::Ok(served.clone())),
+                    _ => panic!("unexpected query"),
+                }
+            }
+            _ => panic!("unexpected query"),
+        });
+        let res = gov.config(&QuerierWrapper::new(&querier)).unwrap();
+        assert_eq!(res, config);
+    }
+
+    #[test]
+    fn test_finalized_deposit_failed() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        let params = AppGovParams {
+            max_deposit_period: Some(50),
+            slash_on_quorum_failure: true,
+            ..AppGovParams::default()
+        };
+        APPGOVPARAMS
+            .save(deps.as_mut().storage, 1, &params)
+            .unwrap();
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(30, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
 
-        // List Votes should show the VoteInfo respected to proposal id.
-        let res = list_votes(deps.as_ref(), id, Option::None, Option::None);
+        env.block.time = env.block.time.plus_seconds(60);
+        let res =
+            execute_finalize(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap();
+        assert_eq!(res.events[0].ty, "proposal_deposit_failed");
+
+        // past `expires` the quorum failed too, the stored status still holds
+        env.block.time = env.block.time.plus_seconds(100);
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert!(prop.quorum_failed());
+        assert_eq!(prop.current_status(&env.block), Status::DepositFailed);
+        let res = query_proposal_detailed(deps.as_ref(), env.clone(), 1).unwrap();
+        assert_eq!(res.status, Status::DepositFailed);
+
+        // and the deposit is refunded as `burn_failed_deposits` is not set
+        let err =
+            execute_slash(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::NotRejected {});
+        let res = execute_refund(deps.as_mut(), env, mock_info("delegator1", &[]), 1).unwrap();
         assert_eq!(
-            res,
-            Ok(VoteListResponse {
-                votes: vec![VoteInfo {
-                    proposal_id: 1,
-                    voter: "admin0001".to_string(),
-                    vote: Vote::Yes,
-                    weight: 10
-                }]
+            res.messages[0].msg,
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: "delegator1".to_string(),
+                amount: coins(30, "ugov"),
             })
         );
     }
 
-    #[allow(non_snake_case)]
     #[test]
-    fn test_deposit_non_gov_token() {
-        // Mock deps
+    fn test_pending_past_expires_deposit_failed() {
         let mut env = mock_env();
-        let mut deps = mock_dependencies1();
-
-        let DENOM: &str = "DNM";
-        env.block.time = Timestamp::from_seconds(1000);
-        env.block.height = 10;
-
-        // Instantiate
-        let imsg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::one(),
-                quorum: Decimal::one(),
-            },
-            locking_contract: Addr::unchecked("locking-contract"),
-        };
-        let sender = Addr::unchecked("sender");
-        let info = mock_info(sender.as_str(), &[]);
-        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
-
-        // Save a proposal
-        let proposal = Proposal {
-            title: "title".to_string(),
-            start_time: Timestamp::from_seconds(1200),
-            description: "description".to_string(),
-            start_height: 10,
-            expires: Expiration::AtHeight(20),
-            msgs: vec![],
+        let prop = Proposal {
             status: Status::Pending,
-            duration: Duration::Height(10),
-            threshold: imsg.threshold,
-            total_weight: 0,
-            votes: Votes {
-                yes: 0,
-                no: 0,
-                abstain: 0,
-                veto: 0,
-            },
-            deposit: vec![],
-            proposer: sender.to_string(),
-            token_denom: DENOM.to_string(),
-            min_deposit: Uint128::from(100_u64),
-            current_deposit: 0,
-            app_mapping_id: 1,
-            is_slashed: false,
+            expires: Expiration::AtTime(env.block.time.plus_seconds(100)),
+            current_deposit: 30,
+            ..test_proposal()
         };
+        assert_eq!(prop.current_status(&env.block), Status::Pending);
 
-        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
-
-        // Deposit gov token
-        let info = mock_info(sender.as_str(), &coins(100, DENOM));
-        let result = execute_deposit(deps.as_mut(), env.clone(), info.clone(), 1).unwrap();
-        assert_eq!(result.messages.len(), 0);
+        // no deposit period set, the voting period bounds the pending phase
+        env.block.time = env.block.time.plus_seconds(100);
+        assert_eq!(prop.current_status(&env.block), Status::DepositFailed);
+    }
 
-        // Check correct update in VOTERDEPOSIT
-        let sender_deposits = VOTERDEPOSIT
-            .load(deps.as_ref().storage, (1, &sender))
+    #[test]
+    fn test_partial_slash_uneven_deposits() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        APPGOVPARAMS
+            .save(
+                deps.as_mut().storage,
+                1,
+                &AppGovParams {
+                    slash_fraction: Some(Decimal::percent(50)),
+                    slash_on_quorum_failure: true,
+                    ..AppGovParams::default()
+                },
+            )
             .unwrap();
-        assert_eq!(sender_deposits.len(), 1);
-        assert_eq!(sender_deposits[0].amount.u128(), 100);
-        assert_eq!(sender_deposits[0].denom, DENOM.to_string());
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(101, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        for depositor in ["delegate", "delegator2"].iter() {
+            execute_deposit(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(depositor, &coins(51, "ugov")),
+                1,
+            )
+            .unwrap();
+        }
 
-        // Check correct update in PROPOSALS
-        let proposal_data = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
-        match proposal_data.status {
-            Status::Pending => {}
-            e => panic!("{:?}", e),
-        };
-        assert_eq!(proposal_data.current_deposit, 100u128);
-        assert_eq!(proposal_data.deposit.len(), 1);
-        assert_eq!(proposal_data.deposit[0].amount.u128(), 100u128);
-        assert_eq!(proposal_data.deposit[0].denom, DENOM.to_string());
+        // each deposit rounds its refund down, the slash takes the rest of it
+        env.block.time = env.block.time.plus_seconds(100);
+        for depositor in ["delegator1", "delegate", "delegator2"].iter() {
+            execute_refund(deps.as_mut(), env.clone(), mock_info(depositor, &[]), 1).unwrap();
+        }
+        let res = execute_slash(deps.as_mut(), env, mock_info("anyone", &[]), 1).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Custom(ComdexMessages::MsgBurnGovTokensForApp {
+                app_id: 1,
+                amount: Coin {
+                    amount: Uint128::from(103_u64),
+                    denom: "ugov".to_string(),
+                },
+                from: "cosmos2contract".to_string(),
+            })
+        );
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(
+            (
+                prop.current_deposit,
+                prop.refunded_deposit,
+                prop.slashed_deposit
+            ),
+            (0, 100, 103)
+        );
+    }
 
-        // Deposit non-gov token
-        let info = mock_info(sender.as_str(), &coins(100, "denom2"));
-        let result = execute_deposit(deps.as_mut(), env.clone(), info, 1).unwrap_err();
+    #[test]
+    fn test_treasury_spends_per_app() {
+        let mut deps = mock_app_dependencies();
+        let env = mock_env();
+        let contract = env.contract.address.clone();
+        let spend = |amount: u128, denom: &str| ProposalMsg::BankSend {
+            to_address: "receiver".to_string(),
+            amount: coins(amount, denom),
+        };
+        execute_fund_treasury(deps.as_mut(), mock_info("funder", &coins(100, "uother")), 2)
+            .unwrap();
+        deps.querier
+            .update_balance(contract.as_str(), coins(100, "uother"));
+
+        // spends of the msgs add up
+        let spends = validate_treasury_spends(
+            deps.as_ref(),
+            &contract,
+            2,
+            &[spend(60, "uother"), spend(40, "uother")],
+        )
+        .unwrap();
+        assert_eq!(spends, coins(100, "uother"));
+        let err = validate_treasury_spends(
+            deps.as_ref(),
+            &contract,
+            2,
+            &[spend(60, "uother"), spend(50, "uother")],
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::SpendExceedsTreasury {});
+        // another app cannot spend the treasury of app 2
+        let err = validate_treasury_spends(deps.as_ref(), &contract, 1, &[spend(10, "uother")])
+            .unwrap_err();
+        assert_eq!(err, ContractError::SpendExceedsTreasury {});
+
+        // a denom also taken as a deposit can be spent beyond the escrowed amount
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            app_proposal(vec![]),
+        )
+        .unwrap();
+        execute_fund_treasury(deps.as_mut(), mock_info("funder", &coins(20, "ugov")), 2).unwrap();
+        deps.querier.update_balance(
+            contract.as_str(),
+            vec![coin(130, "ugov"), coin(100, "uother")],
+        );
+        validate_treasury_spends(deps.as_ref(), &contract, 2, &[spend(20, "ugov")]).unwrap();
+        assert_eq!(
+            query_treasury(deps.as_ref(), 2).unwrap().funds,
+            vec![coin(20, "ugov"), coin(100, "uother")]
+        );
 
-        // Check no update in PROPOSALS
-        let proposal_data = PROPOSALS.load(deps.as_ref().storage, 1).unwrap();
-        assert_eq!(proposal_data.current_deposit, 100);
-        assert_eq!(proposal_data.deposit.len(), 1);
+        // but never the deposits
+        deps.querier.update_balance(
+            contract.as_str(),
+            vec![coin(120, "ugov"), coin(100, "uother")],
+        );
+        let err = execute_propose(
+            deps.as_mut(),
+            env,
+            mock_info("delegator1", &coins(110, "uapp2")),
+            Propose {
+                app_id_param: 2,
+                cosmos_msgs: vec![spend(20, "ugov")],
+                ..app_proposal(vec![])
+            },
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::SpendExceedsTreasury {});
     }
 
     #[test]
-    fn test_refund_invalid_requests() {
-        // Mock deps
+    fn test_burn_capped_by_deposits() {
+        let mut deps = mock_app_dependencies();
         let mut env = mock_env();
-        let mut deps = mock_dependencies1();
-        let sender = Addr::unchecked("sender");
-        let denom: &str = "denom1";
-
-        env.block.time = Timestamp::from_seconds(1000);
-        env.block.height = 10;
-
-        // Instantiate
-        let imsg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::one(),
-                quorum: Decimal::one(),
+        let burn = |amount: u64| ComdexMessages::MsgBurnGovTokensForApp {
+            app_id: 1,
+            amount: Coin {
+                amount: Uint128::from(amount),
+                denom: "ugov".to_string(),
             },
-            locking_contract: Addr::unchecked("locking-contract"),
+            from: "cosmos2contract".to_string(),
         };
-        let sender = Addr::unchecked("sender");
-        let info = mock_info(sender.as_str(), &[]);
-        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
 
-        // Save a proposal
-        let mut proposal = Proposal {
-            title: "title".to_string(),
-            start_time: Timestamp::from_seconds(1200),
-            description: "description".to_string(),
-            start_height: 10,
-            expires: Expiration::AtHeight(20),
-            msgs: vec![],
-            status: Status::Pending,
-            duration: Duration::Height(10),
-            threshold: imsg.threshold,
-            total_weight: 0,
-            votes: Votes {
-                yes: 0,
-                no: 0,
-                abstain: 0,
-                veto: 0,
-            },
-            deposit: vec![],
-            proposer: sender.to_string(),
-            token_denom: denom.to_string(),
-            min_deposit: Uint128::from(100_u64),
-            current_deposit: 0,
-            app_mapping_id: 1,
-            is_slashed: false,
+        // 50 ugov held beside the 110 deposited with the proposal
+        deps.querier
+            .update_balance("cosmos2contract", coins(160, "ugov"));
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            app_proposal(vec![burn(30), burn(20)]),
+        )
+        .unwrap();
+        assert_eq!(
+            ESCROWEDDEPOSITS.load(&deps.storage, "ugov").unwrap(),
+            Uint128::from(110_u64)
+        );
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            1,
+            Vote::Yes,
+        )
+        .unwrap();
+
+        // the balance to burn is checked again on execution
+        env.block.time = env.block.time.plus_seconds(100);
+        deps.querier
+            .update_balance("cosmos2contract", coins(150, "ugov"));
+        let err =
+            execute_execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap_err();
+        assert_eq!(err, ContractError::BurnExceedsTreasury {});
+        deps.querier
+            .update_balance("cosmos2contract", coins(160, "ugov"));
+        execute_execute(deps.as_mut(), env.clone(), mock_info("anyone", &[]), 1).unwrap();
+
+        // the burns of a proposal add up
+        deps.querier
+            .update_balance("cosmos2contract", coins(270, "ugov"));
+        let err = execute_propose(
+            deps.as_mut(),
+            env,
+            mock_info("delegator1", &coins(110, "ugov")),
+            app_proposal(vec![burn(30), burn(21)]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::BurnExceedsTreasury {});
+    }
+
+    #[test]
+    fn test_global_actions_admin_app() {
+        let mut deps = mock_app_dependencies();
+        let env = mock_env();
+        let propose = |actions: Vec<ProposalAction>| Propose {
+            actions,
+            ..app_proposal(vec![])
+        };
+        let guardian = ProposalAction::UpdateGuardian {
+            guardian: Some(Addr::unchecked("guardian")),
         };
 
-        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+        // without an admin app only sudo changes the shared settings
+        let err = execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            propose(vec![guardian.clone()]),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::GlobalActionNotAllowed {});
+        execute_propose(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &coins(110, "ugov")),
+            propose(vec![ProposalAction::UpdateAllowedContracts {
+                add: vec![Addr::unchecked("fee-collector")],
+                remove: vec![],
+            }]),
+        )
+        .unwrap();
 
-        // Execute refund on a pending proposal
-        let info = mock_info(sender.as_str(), &[]);
+        let deps_mut = deps.as_mut();
+        apply_action(
+            deps_mut.storage,
+            deps_mut.api,
+            0,
+            ProposalAction::UpdateAdminApp { app_id: Some(1) },
+        )
+        .unwrap();
+        execute_propose(
+            deps.as_mut(),
+            env,
+            mock_info("delegator1", &coins(110, "ugov")),
+            propose(vec![guardian]),
+        )
+        .unwrap();
+    }
 
-        let result = execute_refund(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
-        match result {
-            ContractError::PendingProposal {} => {}
-            e => panic!("{:?}", e),
+    #[test]
+    fn test_eta_without_time_expiration() {
+        let mut prop = Proposal {
+            expires: Expiration::AtHeight(100),
+            ..test_proposal()
         };
+        assert_eq!(prop.eta(), Expiration::AtHeight(100));
 
-        // Execute refund on an open proposal
-        proposal.status = Status::Open;
-        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
-        let result = execute_refund(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
-        match result {
-            ContractError::OpenProposal {} => {}
-            e => panic!("{:?}", e),
-        };
+        // a delay in seconds cannot be added to a height, the proposal is never executed
+        prop.params.execution_delay = 60;
+        prop.params.execution_grace_period = Some(60);
+        assert_eq!(prop.eta(), Expiration::Never {});
+        assert_eq!(prop.execution_deadline(), Some(Expiration::Never {}));
     }
 
     #[test]
-    fn test_slash_invalid_requests() {
+    fn test_delegated_override_locked() {
+        let mut deps = mock_app_dependencies();
         let mut env = mock_env();
-        let mut deps = mock_dependencies1();
-        env.block.time = Timestamp::from_seconds(100);
-        env.block.height = 10;
-        let denom: &str = "denom1";
-
-        // Instantiate
-        let imsg = InstantiateMsg {
-            threshold: Threshold::ThresholdQuorum {
-                threshold: Decimal::one(),
-                quorum: Decimal::one(),
-            },
-            locking_contract: Addr::unchecked("locking-contract"),
-        };
-        let sender = Addr::unchecked("sender");
-        let info = mock_info(sender.as_str(), &[]);
-        instantiate(deps.as_mut(), env.clone(), info, imsg.clone()).unwrap();
 
-        // Save a proposal
         let proposal = Proposal {
-            title: "title".to_string(),
-            start_time: Timestamp::from_seconds(1200),
-            description: "description".to_string(),
-            start_height: 10,
-            expires: Expiration::AtHeight(20),
-            msgs: vec![],
-            status: Status::Pending,
-            duration: Duration::Height(10),
-            threshold: imsg.threshold,
-            total_weight: 0,
-            votes: Votes {
-                yes: 0,
-                no: 0,
-                abstain: 0,
-                veto: 0,
+            expires: Expiration::AtTime(env.block.time.plus_seconds(100)),
+            params: AppGovParams {
+                vote_lock_period: Some(20),
+                ..AppGovParams::default()
             },
-            deposit: vec![],
-            proposer: sender.to_string(),
-            token_denom: denom.to_string(),
-            min_deposit: Uint128::from(100_u64),
-            current_deposit: 0,
-            app_mapping_id: 1,
-            is_slashed: false,
+            ..test_proposal()
         };
+        proposals()
+            .save(deps.as_mut().storage, 1, &proposal)
+            .unwrap();
 
-        PROPOSALS.save(deps.as_mut().storage, 1, &proposal).unwrap();
+        execute_delegate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &[]),
+            "delegate".to_string(),
+            1,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            1,
+            Vote::Yes,
+        )
+        .unwrap();
 
-        // Slash for pending proposal
-        let info = mock_info(sender.as_str(), &[]);
+        // the delegate's ballot counting the delegator is locked like an own ballot
+        env.block.time = env.block.time.plus_seconds(80);
+        let err = execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &[]),
+            1,
+            Vote::No,
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::VoteLocked {});
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.votes.yes, 30);
+        assert_eq!(prop.votes.no, 0);
+
+        // voters without any ballot can still vote
+        execute_vote(
+            deps.as_mut(),
+            env,
+            mock_info("delegator2", &[]),
+            1,
+            Vote::No,
+        )
+        .unwrap();
+    }
 
-        let result = execute_slash(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
-        match result {
-            ContractError::NotRejected {} => {}
-            e => panic!("{:?}", e),
-        };
+    #[test]
+    fn test_delegated_power_at_start_height() {
+        let mut deps = mock_app_dependencies();
+        let mut env = mock_env();
+        proposals()
+            .save(deps.as_mut().storage, 1, &test_proposal())
+            .unwrap();
 
-        // Slash for rejected proposal but not vetoed
-        env.block.time = Timestamp::from_seconds(5000);
-        env.block.height = 30;
-        let info = mock_info(sender.as_str(), &[]);
+        // delegated after the start, counted with the power held at the start
+        env.block.height += 100;
+        for delegator in ["delegator1", "delegator3"] {
+            execute_delegate(
+                deps.as_mut(),
+                env.clone(),
+                mock_info(delegator, &[]),
+                "delegate".to_string(),
+                1,
+            )
+            .unwrap();
+        }
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegate", &[]),
+            1,
+            Vote::Yes,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.votes.yes, 30);
+
+        // a re-delegated delegator is not counted again by the new delegate
+        execute_delegate(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator1", &[]),
+            "delegator2".to_string(),
+            1,
+        )
+        .unwrap();
+        execute_vote(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("delegator2", &[]),
+            1,
+            Vote::No,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.votes.yes, 30);
+        assert_eq!(prop.votes.no, 30);
 
-        let result = execute_slash(deps.as_mut(), env.clone(), info.clone(), 1).unwrap_err();
-        match result {
-            ContractError::ProposalNotVetoed {} => {}
-            e => panic!("{:?}", e),
-        };
+        // its own vote still takes the weight back from the first delegate
+        execute_vote(
+            deps.as_mut(),
+            env,
+            mock_info("delegator1", &[]),
+            1,
+            Vote::Veto,
+        )
+        .unwrap();
+        let prop = proposals().load(&deps.storage, 1).unwrap();
+        assert_eq!(prop.votes.yes, 10);
+        assert_eq!(prop.votes.no, 30);
+        assert_eq!(prop.votes.veto, 20);
     }
 }
@@ -129,4 +129,109 @@ pub enum ContractError {
 
     #[error("Proposal is already slashed")]
     AlreadySlashed {},
+
+    #[error("Cannot delegate voting power to yourself")]
+    SelfDelegation {},
+
+    #[error("No delegation found for app")]
+    NoDelegation {},
+
+    #[error("Delegate has reached the maximum number of delegators")]
+    TooManyDelegators {},
+
+    #[error("Proposal can only be cancelled while pending or before others voted")]
+    CannotCancel {},
+
+    #[error("Execution delay of the proposal has not passed yet")]
+    TimelockNotExpired {},
+
+    #[error("Execution window of the proposal has expired")]
+    ExecutionWindowExpired {},
+
+    #[error("Execution grace period cannot be zero")]
+    ZeroGracePeriod {},
+
+    #[error("Execution delay and grace period need a proposal that expires at a time")]
+    TimelockNeedsTimeExpiration {},
+
+    #[error("Only passed proposals can be vetoed")]
+    NotPassed {},
+
+    #[error("Veto threshold must be above 0 and at most 100%")]
+    InvalidVetoThreshold {},
+
+    #[error("Voting period cannot be zero")]
+    ZeroVotingPeriod {},
+
+    #[error("Contract is not allowed for the app")]
+    ContractNotAllowed {},
+
+    #[error("Treasury spends must be non zero")]
+    InvalidTreasurySpend {},
+
+    #[error("Spend exceeds the app's treasury held beyond the proposal deposits")]
+    SpendExceedsTreasury {},
+
+    #[error("Only a non zero amount of the app's gov token held by this contract can be burnt")]
+    InvalidBurn {},
+
+    #[error("Burn exceeds the gov tokens held beyond the proposal deposits")]
+    BurnExceedsTreasury {},
+
+    #[error("Only proposals of the admin app can change settings shared by all apps")]
+    GlobalActionNotAllowed {},
+
+    #[error("Multiple choice proposals need between 2 and 10 choices and no msgs of their own")]
+    InvalidChoices {},
+
+    #[error("Choice does not exist for the proposal")]
+    InvalidChoice {},
+
+    #[error("Yes votes on multiple choice proposals must pick a choice")]
+    ChoiceRequired {},
+
+    #[error("Proposal is not a multiple choice proposal")]
+    NotMultipleChoice {},
+
+    #[error("Vote options must be distinct, non zero and add up to 100%")]
+    InvalidVoteOptions {},
+
+    #[error("Votes can no longer be changed in the final window of the proposal")]
+    VoteLocked {},
+
+    #[error("Vote lock period must be shorter than the voting period")]
+    InvalidVoteLockPeriod {},
+
+    #[error("Proposer does not hold enough vtokens to propose")]
+    InsufficientProposerPower {},
+
+    #[error("Initial deposit is below the required share of the min deposit")]
+    InsufficientInitialDeposit {},
+
+    #[error("Proposer has reached the maximum number of open proposals")]
+    TooManyOpenProposals {},
+
+    #[error("Initial deposit share must be above 0 and at most 100%")]
+    InvalidInitialDeposit {},
+
+    #[error("Deposit period cannot be zero")]
+    ZeroDepositPeriod {},
+
+    #[error("Slash fraction must be above 0 and at most 1")]
+    InvalidSlashFraction {},
+
+    #[error("Proposal is still pending or open")]
+    VotingNotEnded {},
+
+    #[error("Proposal is already finalized")]
+    AlreadyFinalized {},
+
+    #[error("Cannot close completed or passed proposals")]
+    WrongCloseStatus {},
+
+    #[error("No slashed deposit to claim")]
+    NothingToClaim {},
+
+    #[error("Slashed deposit already claimed")]
+    AlreadyClaimed {},
 }
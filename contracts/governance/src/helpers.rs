@@ -0,0 +1,126 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use comdex_bindings::{ComdexMessages, ComdexQuery};
+use cosmwasm_std::{
+    to_binary, Addr, Coin, CosmosMsg, QuerierWrapper, QueryRequest, StdResult, WasmMsg, WasmQuery,
+};
+use cw3::{Status, Vote};
+
+use crate::msg::{AppProposalResponse, ExecuteMsg, ProposalResponseTotal, Propose, QueryMsg};
+use crate::state::{AppGovConfigResponse, Config};
+
+/// GovernanceContract is a wrapper around Addr that provides helpers
+/// for contracts working with governance
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GovernanceContract(pub Addr);
+
+impl GovernanceContract {
+    pub fn new(addr: Addr) -> Self {
+        GovernanceContract(addr)
+    }
+
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    pub fn encode_msg(
+        &self,
+        msg: ExecuteMsg,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg<ComdexMessages>> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&msg)?,
+            funds,
+        }
+        .into())
+    }
+
+    fn encode_smart_query(&self, msg: QueryMsg) -> StdResult<QueryRequest<ComdexQuery>> {
+        Ok(WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&msg)?,
+        }
+        .into())
+    }
+
+    /// funds are the initial deposit of the proposal
+    pub fn propose(
+        &self,
+        propose: Propose,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg<ComdexMessages>> {
+        self.encode_msg(ExecuteMsg::Propose { propose }, funds)
+    }
+
+    pub fn vote(&self, proposal_id: u64, vote: Vote) -> StdResult<CosmosMsg<ComdexMessages>> {
+        self.encode_msg(ExecuteMsg::Vote { proposal_id, vote }, vec![])
+    }
+
+    pub fn deposit(
+        &self,
+        proposal_id: u64,
+        funds: Vec<Coin>,
+    ) -> StdResult<CosmosMsg<ComdexMessages>> {
+        self.encode_msg(ExecuteMsg::Deposit { proposal_id }, funds)
+    }
+
+    pub fn execute(&self, proposal_id: u64) -> StdResult<CosmosMsg<ComdexMessages>> {
+        self.encode_msg(ExecuteMsg::Execute { proposal_id }, vec![])
+    }
+
+    pub fn refund(&self, proposal_id: u64) -> StdResult<CosmosMsg<ComdexMessages>> {
+        self.encode_msg(ExecuteMsg::Refund { proposal_id }, vec![])
+    }
+
+    pub fn slash(&self, proposal_id: u64) -> StdResult<CosmosMsg<ComdexMessages>> {
+        self.encode_msg(ExecuteMsg::Slash { proposal_id }, vec![])
+    }
+
+    pub fn proposal(
+        &self,
+        querier: &QuerierWrapper<ComdexQuery>,
+        proposal_id: u64,
+    ) -> StdResult<ProposalResponseTotal> {
+        let query = self.encode_smart_query(QueryMsg::Proposal { proposal_id })?;
+        querier.query(&query)
+    }
+
+    /// Proposals of the app, newest first, paged with `start_before`
+    pub fn list_app_proposals(
+        &self,
+        querier: &QuerierWrapper<ComdexQuery>,
+        app_id: u64,
+        start_before: Option<u64>,
+        limit: Option<u32>,
+        status: Option<Status>,
+    ) -> StdResult<AppProposalResponse> {
+        let query = self.encode_smart_query(QueryMsg::ListAppProposal {
+            app_id,
+            start_after: None,
+            start_before,
+            limit,
+            ascending: false,
+            status,
+            proposer: None,
+            start_time_from: None,
+            start_time_to: None,
+        })?;
+        querier.query(&query)
+    }
+
+    pub fn participation_stats(
+        &self,
+        querier: &QuerierWrapper<ComdexQuery>,
+        app_id: u64,
+    ) -> StdResult<AppGovConfigResponse> {
+        let query = self.encode_smart_query(QueryMsg::ParticipationStats { app_id })?;
+        querier.query(&query)
+    }
+
+    pub fn config(&self, querier: &QuerierWrapper<ComdexQuery>) -> StdResult<Config> {
+        let query = self.encode_smart_query(QueryMsg::Config {})?;
+        querier.query(&query)
+    }
+}
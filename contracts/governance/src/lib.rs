@@ -1,6 +1,8 @@
 pub mod coin_helpers;
 pub mod contract;
 mod error;
+pub mod helpers;
+pub mod locking;
 pub mod msg;
 pub mod state;
 pub mod validation;
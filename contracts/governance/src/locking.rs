@@ -0,0 +1,76 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{
+    to_binary, Addr, CustomQuery, QuerierWrapper, QueryRequest, StdResult, Uint128, WasmQuery,
+};
+
+use crate::state::TokenSupply;
+
+/// Queries of the locking contract that governance relies on
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum LockingQueryMsg {
+    /// Returns TokenSupply
+    Supply { denom: String },
+    /// Returns Uint128, the vtokens of the address
+    TotalVTokens {
+        address: Addr,
+        denom: String,
+        height: Option<u64>,
+    },
+}
+
+/// LockingContract is a wrapper around Addr that provides helpers
+/// for querying the locking contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockingContract(pub Addr);
+
+impl LockingContract {
+    pub fn new(addr: Addr) -> Self {
+        LockingContract(addr)
+    }
+
+    pub fn addr(&self) -> Addr {
+        self.0.clone()
+    }
+
+    fn encode_smart_query<Q: CustomQuery>(
+        &self,
+        msg: LockingQueryMsg,
+    ) -> StdResult<QueryRequest<Q>> {
+        Ok(WasmQuery::Smart {
+            contract_addr: self.addr().into(),
+            msg: to_binary(&msg)?,
+        }
+        .into())
+    }
+
+    /// Total tokens locked and vtokens issued for the denom
+    pub fn supply<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        denom: impl Into<String>,
+    ) -> StdResult<TokenSupply> {
+        let query = self.encode_smart_query(LockingQueryMsg::Supply {
+            denom: denom.into(),
+        })?;
+        querier.query(&query)
+    }
+
+    /// vtokens of the address at the given height, the latest if not set
+    pub fn voting_power<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        address: &Addr,
+        denom: impl Into<String>,
+        height: Option<u64>,
+    ) -> StdResult<Uint128> {
+        let query = self.encode_smart_query(LockingQueryMsg::TotalVTokens {
+            address: address.clone(),
+            denom: denom.into(),
+            height,
+        })?;
+        querier.query(&query)
+    }
+}
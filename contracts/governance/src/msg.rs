@@ -1,6 +1,6 @@
-use crate::state::Votes;
+use crate::state::{AppGovParams, Votes};
 use comdex_bindings::ComdexMessages;
-use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
+use cosmwasm_std::{Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, Timestamp, Uint128, WasmMsg};
 use cw3::{Status, Vote};
 use cw_utils::{Duration, Expiration, Threshold};
 use schemars::JsonSchema;
@@ -10,6 +10,9 @@ use serde::{Deserialize, Serialize};
 pub struct InstantiateMsg {
     pub threshold: Threshold,
     pub locking_contract: Addr,
+    // defaults to 33% if not set
+    #[serde(default)]
+    pub veto_threshold: Option<Decimal>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq)]
@@ -32,6 +35,8 @@ pub struct ProposalResponseTotal {
     pub duration: Duration,
     /// pass requirements
     pub threshold: Threshold,
+    // share of veto votes above which the proposal is vetoed
+    pub veto_threshold: Decimal,
     // the total weight when the proposal started (used to calculate percentages)
     pub total_weight: u128,
     // summary of existing votes
@@ -39,6 +44,16 @@ pub struct ProposalResponseTotal {
     pub proposer: String,
     pub token_denom: String,
     pub current_deposit: u128,
+    pub refunded_deposit: u128,
+    pub slashed_deposit: u128,
+    pub end_time: Option<Timestamp>,
+    // earliest point of execution once passed
+    pub eta: Expiration,
+    pub cosmos_msgs: Vec<ProposalMsg>,
+    pub actions: Vec<ProposalAction>,
+    pub choices: Vec<ProposalChoice>,
+    // weight of the yes votes for each choice
+    pub choice_votes: Vec<u128>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -49,12 +64,191 @@ pub struct Propose {
     // note: we ignore API-spec'd earliest if passed, always opens immediately
     pub latest: Option<Expiration>,
     pub app_id_param: u64,
+    // bank and wasm messages, dispatched after `msgs`
+    #[serde(default)]
+    pub cosmos_msgs: Vec<ProposalMsg>,
+    // changes to this contract, applied on execution after `msgs` are dispatched
+    #[serde(default)]
+    pub actions: Vec<ProposalAction>,
+    // makes it a multiple choice proposal, `msgs` must then be empty
+    #[serde(default)]
+    pub choices: Vec<ProposalChoice>,
+}
+
+/// One option of a multiple choice proposal
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProposalChoice {
+    pub title: String,
+    pub msgs: Vec<ComdexMessages>,
+}
+
+/// Messages beside the Comdex module ones a proposal can dispatch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalMsg {
+    /// Spend from the treasury of the app of the proposal
+    BankSend {
+        to_address: String,
+        amount: Vec<Coin>,
+    },
+    /// Only against contracts allowed for the app of the proposal
+    WasmExecute {
+        contract_addr: String,
+        msg: Binary,
+        funds: Vec<Coin>,
+    },
+    /// Only against contracts allowed for the app of the proposal
+    WasmMigrate {
+        contract_addr: String,
+        new_code_id: u64,
+        msg: Binary,
+    },
+}
+
+impl ProposalMsg {
+    /// funds the msg takes from the treasury
+    pub fn funds(&self) -> &[Coin] {
+        match self {
+            ProposalMsg::BankSend { amount, .. } => amount,
+            ProposalMsg::WasmExecute { funds, .. } => funds,
+            ProposalMsg::WasmMigrate { .. } => &[],
+        }
+    }
+}
+
+impl From<ProposalMsg> for CosmosMsg<ComdexMessages> {
+    fn from(msg: ProposalMsg) -> Self {
+        match msg {
+            ProposalMsg::BankSend { to_address, amount } => {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount })
+            }
+            ProposalMsg::WasmExecute {
+                contract_addr,
+                msg,
+                funds,
+            } => CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr,
+                msg,
+                funds,
+            }),
+            ProposalMsg::WasmMigrate {
+                contract_addr,
+                new_code_id,
+                msg,
+            } => CosmosMsg::Wasm(WasmMsg::Migrate {
+                contract_addr,
+                new_code_id,
+                msg,
+            }),
+        }
+    }
+}
+
+/// Changes to the governance contract itself, the proposal counterpart of `SudoMsg`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalAction {
+    UpdateThreshold {
+        threshold: Threshold,
+        veto_threshold: Option<Decimal>,
+    },
+    UpdateLockingContract {
+        address: Addr,
+    },
+    /// Only for the app of the proposal
+    UpdateAppGovParams {
+        params: Box<AppGovParams>,
+    },
+    UpdateGuardian {
+        guardian: Option<Addr>,
+    },
+    /// Only for the app of the proposal
+    UpdateAllowedContracts {
+        add: Vec<Addr>,
+        remove: Vec<Addr>,
+    },
+    /// App whose proposals may change the settings shared by all apps
+    UpdateAdminApp {
+        app_id: Option<u64>,
+    },
+}
+
+impl ProposalAction {
+    /// Returns true for changes to settings shared by all apps,
+    /// only proposals of the admin app and sudo may make them
+    pub fn is_global(&self) -> bool {
+        !matches!(
+            self,
+            ProposalAction::UpdateAppGovParams { .. }
+                | ProposalAction::UpdateAllowedContracts { .. }
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct AppProposalResponse {
     pub proposals: Vec<ProposalResponseTotal>,
+    // all proposals of the app, regardless of the filters
     pub proposal_count: u64,
+    // id to continue from as `start_after`, or `start_before` when descending,
+    // set when the listing stopped before reading every match
+    #[serde(default)]
+    pub next: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DelegationResponse {
+    pub delegate: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DelegatedPowerResponse {
+    pub delegators: u64,
+    // sum of the delegators' vtokens at the queried height
+    pub power: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct VoteAuditResponse {
+    pub ballots: u64,
+    // tally stored with the proposal
+    pub stored: Votes,
+    // tally recounted from the ballots
+    pub computed: Votes,
+    pub stored_choice_votes: Vec<u128>,
+    pub computed_choice_votes: Vec<u128>,
+    pub matches: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DepositInfo {
+    pub proposal_id: u64,
+    pub depositor: String,
+    pub amount: Vec<Coin>,
+    /// part of the deposit taken by a slash, `amount` is what is left of it
+    #[serde(default)]
+    pub slashed: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DepositResponse {
+    pub deposit: Option<DepositInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DepositListResponse {
+    pub deposits: Vec<DepositInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct AllowedContractsResponse {
+    pub contracts: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TreasuryResponse {
+    // funded and not spent yet, the contract may hold less once deposits are taken out
+    pub funds: Vec<Coin>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -72,20 +266,86 @@ pub struct ExtendedPair {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    Propose { propose: Propose },
-    Vote { proposal_id: u64, vote: Vote },
-    Execute { proposal_id: u64 },
-    Refund { proposal_id: u64 },
+    Propose {
+        propose: Propose,
+    },
+    Vote {
+        proposal_id: u64,
+        vote: Vote,
+    },
+    /// Splits the voting power across several options
+    VoteWeighted {
+        proposal_id: u64,
+        options: Vec<(Vote, Decimal)>,
+    },
+    /// Yes vote for a choice of a multiple choice proposal,
+    /// no, abstain and veto go through `Vote`
+    VoteChoice {
+        proposal_id: u64,
+        choice: u32,
+    },
+    Execute {
+        proposal_id: u64,
+    },
+    Refund {
+        proposal_id: u64,
+    },
+    /// Refunds up to `limit` depositors of the proposal, call again to continue
+    RefundAll {
+        proposal_id: u64,
+        limit: Option<u32>,
+    },
 
-    Deposit { proposal_id: u64 },
-    Slash { proposal_id: u64 },
+    Deposit {
+        proposal_id: u64,
+    },
+    Slash {
+        proposal_id: u64,
+    },
+    /// Pays the sender's share of a deposit slashed to the No and Veto voters
+    ClaimSlashed {
+        proposal_id: u64,
+    },
+    /// Adds the sent funds to the treasury of `app_id`, spent by its proposals
+    FundTreasury {
+        app_id: u64,
+    },
+    /// Hand the sender's voting power for `app_id` over to `delegate`
+    Delegate {
+        delegate: String,
+        app_id: u64,
+    },
+    Undelegate {
+        app_id: u64,
+    },
+    /// Withdraw a proposal before anyone but the proposer voted on it
+    Cancel {
+        proposal_id: u64,
+    },
+    /// Guardian only, stops a passed proposal from being executed
+    GuardianVeto {
+        proposal_id: u64,
+    },
+    /// Stores the final status of a proposal whose voting has ended
+    Finalize {
+        proposal_id: u64,
+    },
+    /// Finalizes up to `limit` proposals of the app
+    FinalizeExpired {
+        app_id: u64,
+        limit: Option<u32>,
+    },
+    /// cw3 close, rejects an expired proposal that did not pass
+    Close {
+        proposal_id: u64,
+    },
 }
 
 // We can also add this as a cw3 extension
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    /// Return ThresholdResponse
+    /// Returns ThresholdResponse, the veto threshold is part of ProposalResponse
     Threshold {
         proposal_id: u64,
     },
@@ -114,19 +374,30 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
+    /// Returns AppProposalResponse, newest first unless `ascending` is set
     ListAppProposal {
         app_id: u64,
-        start_after: u32,
+        // proposal id cursors, both exclusive
+        start_after: Option<u64>,
+        start_before: Option<u64>,
         limit: Option<u32>,
+        #[serde(default)]
+        ascending: bool,
         status: Option<Status>,
+        proposer: Option<String>,
+        // bounds on `start_time`, both inclusive
+        start_time_from: Option<Timestamp>,
+        start_time_to: Option<Timestamp>,
     },
 
     AppAllUpData {
         app_id: u64,
     },
+    /// Returns TokenSupply, proxied from the locking contract
     Supply {
         denom: String,
     },
+    /// Returns Uint128, the vtokens of the address proxied from the locking contract
     TotalVTokens {
         address: Addr,
         denom: String,
@@ -136,15 +407,87 @@ pub enum QueryMsg {
     ParticipationStats {
         app_id: u64,
     },
+    /// Returns AppGovParams
+    AppGovParams {
+        app_id: u64,
+    },
+    /// Returns DelegationResponse
+    Delegation {
+        delegator: String,
+        app_id: u64,
+    },
+    /// Returns DelegatedPowerResponse
+    DelegatedPower {
+        delegate: String,
+        app_id: u64,
+        height: Option<u64>,
+    },
+    /// Returns ProposalListResponse, proposals of the address in all apps
+    ProposalsByProposer {
+        proposer: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns VoteListResponse, ballots of the address on the app's proposals
+    VotesByVoter {
+        voter: String,
+        app_id: u64,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// Returns VoteAuditResponse, recounts every ballot of the proposal
+    VoteAudit {
+        proposal_id: u64,
+    },
+    /// Returns AllowedContractsResponse
+    AllowedContracts {
+        app_id: u64,
+    },
+    /// Returns TreasuryResponse
+    Treasury {
+        app_id: u64,
+    },
+    /// Returns DepositResponse
+    Deposit {
+        proposal_id: u64,
+        depositor: String,
+    },
+    /// Returns DepositListResponse
+    ListDeposits {
+        proposal_id: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SudoMsg {
-    UpdateLockingContract { address: Addr },
-    UpdateThreshold { threshold: Threshold },
+    UpdateLockingContract {
+        address: Addr,
+    },
+    UpdateThreshold {
+        threshold: Threshold,
+        veto_threshold: Option<Decimal>,
+    },
+    UpdateAppGovParams {
+        app_id: u64,
+        params: Box<AppGovParams>,
+    },
+    UpdateGuardian {
+        guardian: Option<Addr>,
+    },
+    UpdateAllowedContracts {
+        app_id: u64,
+        add: Vec<Addr>,
+        remove: Vec<Addr>,
+    },
+    UpdateAdminApp {
+        app_id: Option<u64>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {
-    pub refund_address: Addr}
+    pub refund_address: Addr,
+}
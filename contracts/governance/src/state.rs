@@ -1,9 +1,10 @@
 use std::ops::Mul;
 
+use crate::msg::{ProposalAction, ProposalChoice, ProposalMsg};
 use comdex_bindings::ComdexMessages;
-use cosmwasm_std::{Addr, BlockInfo, Coin, Decimal, StdResult, Storage, Timestamp, Uint128};
+use cosmwasm_std::{Addr, BlockInfo, Coin, Decimal, Empty, StdResult, Storage, Timestamp, Uint128};
 use cw3::{Status, Vote};
-use cw_storage_plus::{Item, Map};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 use cw_utils::{Duration, Expiration, Threshold};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -15,7 +16,105 @@ const PRECISION_FACTOR: u128 = 1_000_000_000;
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Config {
     pub threshold: Threshold,
+    // share of veto votes above which a proposal is vetoed, apps can override it
+    #[serde(default = "default_veto_threshold")]
+    pub veto_threshold: Decimal,
     pub locking_contract: Addr,
+    // can veto passed proposals before they are executed
+    #[serde(default)]
+    pub guardian: Option<Addr>,
+    // app whose proposals may change the settings above, sudo only if none
+    #[serde(default)]
+    pub admin_app_id: Option<u64>,
+}
+
+// veto share used when none is configured
+pub const DEFAULT_VETO_THRESHOLD: u64 = 33;
+
+fn default_veto_threshold() -> Decimal {
+    Decimal::percent(DEFAULT_VETO_THRESHOLD)
+}
+
+/// Where slashed deposits go
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SlashDestination {
+    #[default]
+    Burn,
+    CommunityPool {
+        address: Addr,
+    },
+    /// split among No and Veto voters by the weight of their votes
+    NoVetoVoters,
+}
+
+/// Governance parameters that can be set per app
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq, Default)]
+pub struct AppGovParams {
+    /// overrides the threshold of `Config.threshold`
+    #[serde(default)]
+    pub threshold: Option<Decimal>,
+    /// overrides the quorum of `Config.threshold`
+    #[serde(default)]
+    pub quorum: Option<Decimal>,
+    /// overrides `Config.veto_threshold`
+    #[serde(default)]
+    pub veto_threshold: Option<Decimal>,
+    /// overrides the app's `min_gov_deposit`
+    #[serde(default)]
+    pub min_deposit: Option<Uint128>,
+    /// overrides the app's `gov_time_in_seconds`
+    #[serde(default)]
+    pub max_voting_period: Option<u64>,
+    /// seconds a passed proposal has to wait after `expires` before it can be executed
+    #[serde(default)]
+    pub execution_delay: u64,
+    /// seconds after the delay during which a passed proposal can still be executed,
+    /// no limit if not set
+    #[serde(default)]
+    pub execution_grace_period: Option<u64>,
+    /// whether ballots can be changed, defaults to true
+    #[serde(default)]
+    pub allow_revoting: Option<bool>,
+    /// seconds before `expires` during which ballots can no longer be changed
+    #[serde(default)]
+    pub vote_lock_period: Option<u64>,
+    /// vtokens the proposer needs at the proposal height
+    #[serde(default)]
+    pub min_proposer_power: Option<Uint128>,
+    /// share of the min deposit that has to be sent with the proposal
+    #[serde(default)]
+    pub min_initial_deposit: Option<Decimal>,
+    /// Pending or Open proposals a proposer can have at once
+    #[serde(default)]
+    pub max_open_proposals: Option<u32>,
+    /// seconds a Pending proposal has to reach the min deposit, until `expires` if not set
+    #[serde(default)]
+    pub max_deposit_period: Option<u64>,
+    /// deposits of proposals that failed to reach the min deposit are slashed instead of refunded
+    #[serde(default)]
+    pub burn_failed_deposits: bool,
+    /// share of a deposit that is slashed, the rest stays refundable, all of it if not set
+    #[serde(default)]
+    pub slash_fraction: Option<Decimal>,
+    #[serde(default)]
+    pub slash_destination: SlashDestination,
+    /// also slash proposals rejected because they did not reach the quorum
+    #[serde(default)]
+    pub slash_on_quorum_failure: bool,
+}
+
+impl AppGovParams {
+    /// Applies the threshold and quorum overrides to the global threshold
+    pub fn apply_threshold(&self, threshold: &Threshold) -> Threshold {
+        match threshold {
+            Threshold::ThresholdQuorum { threshold, quorum } => Threshold::ThresholdQuorum {
+                threshold: self.threshold.unwrap_or(*threshold),
+                quorum: self.quorum.unwrap_or(*quorum),
+            },
+            threshold => threshold.clone(),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Eq)]
@@ -74,7 +173,40 @@ pub struct Proposal {
     pub min_deposit: Uint128,
     pub current_deposit: u128,
     pub app_mapping_id: u64,
+    // share of veto votes above which the proposal is vetoed
+    #[serde(default = "default_veto_threshold")]
+    pub veto_threshold: Decimal,
     pub is_slashed: bool,
+    // deposits paid back to depositors, taken off `current_deposit`
+    #[serde(default)]
+    pub refunded_deposit: u128,
+    // deposits burnt by `Slash`, taken off `current_deposit`
+    #[serde(default)]
+    pub slashed_deposit: u128,
+    // share of the deposits a slash takes, rounded per depositor
+    #[serde(default)]
+    pub slashable_deposit: u128,
+    // slashed deposit left for No and Veto voters to claim
+    #[serde(default)]
+    pub voter_reward: u128,
+    // set once `Finalize` stored the final status
+    #[serde(default)]
+    pub end_time: Option<Timestamp>,
+    // bank and wasm messages dispatched after `msgs`
+    #[serde(default)]
+    pub cosmos_msgs: Vec<ProposalMsg>,
+    // changes to this contract, applied on execution
+    #[serde(default)]
+    pub actions: Vec<ProposalAction>,
+    // options of a multiple choice proposal, the winning one replaces `msgs`
+    #[serde(default)]
+    pub choices: Vec<ProposalChoice>,
+    // weight of the yes votes for each choice
+    #[serde(default)]
+    pub choice_votes: Vec<u128>,
+    // app params at the time the proposal was created
+    #[serde(default)]
+    pub params: AppGovParams,
 }
 
 impl Proposal {
@@ -83,10 +215,21 @@ impl Proposal {
     pub fn current_status(&self, block: &BlockInfo) -> Status {
         let mut status = self.status;
 
-        if status == Status::Executed {
-            status = Status::Executed;
-        } else if status == Status::Pending && self.expires.is_expired(block) {
-            status = Status::Rejected;
+        if self.end_time.is_some()
+            || [
+                Status::Executed,
+                Status::Cancelled,
+                Status::Vetoed,
+                Status::DepositFailed,
+            ]
+            .contains(&status)
+        {
+            // finalized or terminal status, stays as stored
+        } else if status == Status::Pending
+            && (self.deposit_period_expired(block) || self.expires.is_expired(block))
+        {
+            // never reached the min deposit
+            status = Status::DepositFailed;
         } else if self.expires.is_expired(block) && self.is_passed(block) {
             status = Status::Passed;
         } else if self.expires.is_expired(block) && self.is_rejected(block) {
@@ -102,9 +245,195 @@ impl Proposal {
         self.status = self.current_status(block);
     }
 
+    /// Earliest point at which a passed proposal can be executed, the delay is in
+    /// seconds so a proposal not expiring at a time is never executed with one
+    pub fn eta(&self) -> Expiration {
+        match self.expires {
+            Expiration::AtTime(time) => {
+                Expiration::AtTime(time.plus_seconds(self.params.execution_delay))
+            }
+            _ if self.params.execution_delay > 0 => Expiration::Never {},
+            expires => expires,
+        }
+    }
+
+    /// Point after which a passed proposal can no longer be executed, if any
+    pub fn execution_deadline(&self) -> Option<Expiration> {
+        self.params
+            .execution_grace_period
+            .map(|grace_period| match self.eta() {
+                Expiration::AtTime(time) => Expiration::AtTime(time.plus_seconds(grace_period)),
+                eta => eta,
+            })
+    }
+
+    /// adds a ballot to the tally, yes votes of multiple choice proposals also count for their choice
+    pub fn add_ballot(&mut self, ballot: &Ballot) {
+        self.votes.add_weights(&ballot.vote_weight());
+        if let Some(choice) = ballot.choice {
+            self.choice_votes[choice as usize] += ballot.weight;
+        }
+    }
+
+    pub fn subtract_ballot(&mut self, ballot: &Ballot) {
+        self.votes.subtract_weights(&ballot.vote_weight());
+        if let Some(choice) = ballot.choice {
+            self.choice_votes[choice as usize] -= ballot.weight;
+        }
+    }
+
+    /// Index of the choice with the most weight, none on a tie or if nobody picked a choice
+    pub fn winning_choice(&self) -> Option<usize> {
+        let max = self.choice_votes.iter().copied().max().unwrap_or_default();
+        let mut leaders = self
+            .choice_votes
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight == max);
+        match (leaders.next(), leaders.next()) {
+            (Some((choice, _)), None) if max > 0 => Some(choice),
+            _ => None,
+        }
+    }
+
+    /// Returns true once the deposit period of the app has passed
+    pub fn deposit_period_expired(&self, block: &BlockInfo) -> bool {
+        match self.params.max_deposit_period {
+            Some(deposit_period) => block.time >= self.start_time.plus_seconds(deposit_period),
+            None => false,
+        }
+    }
+
+    /// Adds a deposit on top of what the depositor already deposited,
+    /// `deposit` keeps a single total per denom
+    pub fn add_deposit(&mut self, previous: &[Coin], coin: &Coin) {
+        match self.deposit.iter_mut().find(|c| c.denom == coin.denom) {
+            Some(total) => total.amount += coin.amount,
+            None => self.deposit.push(coin.clone()),
+        }
+        if coin.denom == self.token_denom {
+            self.current_deposit += coin.amount.u128();
+            let previous = previous
+                .iter()
+                .find(|c| c.denom == coin.denom)
+                .map(|c| c.amount)
+                .unwrap_or_default();
+            self.slashable_deposit +=
+                self.slashed_part(previous + coin.amount) - self.slashed_part(previous);
+        }
+    }
+
+    /// Returns the part of a single deposit that a slash takes
+    pub fn slashed_part(&self, amount: Uint128) -> u128 {
+        (amount - amount * (Decimal::one() - self.slash_fraction())).u128()
+    }
+
+    /// Moves a deposit paid back to its depositor out of `current_deposit`
+    /// and returns the amount of the gov token
+    pub fn refund_deposit(&mut self, coins: &[Coin]) -> u128 {
+        let amount: u128 = coins
+            .iter()
+            .filter(|c| c.denom == self.token_denom)
+            .map(|c| c.amount.u128())
+            .sum();
+        self.current_deposit = self.current_deposit.saturating_sub(amount);
+        self.refunded_deposit += amount;
+        amount
+    }
+
+    /// Returns true if the deposit is to be slashed rather than refunded in full
+    pub fn is_slashable(&self, block: &BlockInfo) -> bool {
+        match self.current_status(block) {
+            Status::Rejected => {
+                self.check_vetoed(block)
+                    || (self.params.slash_on_quorum_failure && self.quorum_failed())
+            }
+            Status::DepositFailed => self.params.burn_failed_deposits,
+            _ => false,
+        }
+    }
+
+    /// Returns true if the votes cast did not reach the quorum
+    pub fn quorum_failed(&self) -> bool {
+        match self.threshold {
+            Threshold::ThresholdQuorum { quorum, .. } => {
+                self.votes.total() < votes_needed(self.total_weight, quorum)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn slash_fraction(&self) -> Decimal {
+        self.params.slash_fraction.unwrap_or_else(Decimal::one)
+    }
+
+    /// Returns the part of a deposit that is paid back to its depositor
+    pub fn refundable(&self, block: &BlockInfo, coins: &[Coin]) -> Vec<Coin> {
+        if !self.is_slashable(block) {
+            return coins.to_vec();
+        }
+        self.split_deposit(coins).0
+    }
+
+    /// Splits a deposit into the part paid back and the part taken by a slash
+    pub fn split_deposit(&self, coins: &[Coin]) -> (Vec<Coin>, Vec<Coin>) {
+        let share = Decimal::one() - self.slash_fraction();
+        let mut refundable = vec![];
+        let mut slashed = vec![];
+        for coin in coins {
+            let refund = coin.amount * share;
+            if !refund.is_zero() {
+                refundable.push(Coin {
+                    denom: coin.denom.clone(),
+                    amount: refund,
+                });
+            }
+            if refund < coin.amount {
+                slashed.push(Coin {
+                    denom: coin.denom.clone(),
+                    amount: coin.amount - refund,
+                });
+            }
+        }
+        (refundable, slashed)
+    }
+
+    /// Moves the slashed share of all deposits to `slashed_deposit` and returns the amount
+    pub fn slash_deposit(&mut self) -> u128 {
+        // nothing was refunded when all of it is slashed
+        let amount = if self.slash_fraction() == Decimal::one() {
+            self.current_deposit
+        } else {
+            self.slashable_deposit.min(self.current_deposit)
+        };
+        self.current_deposit -= amount;
+        self.slashed_deposit += amount;
+        self.is_slashed = true;
+        if self.params.slash_destination == SlashDestination::NoVetoVoters
+            && self.votes.no + self.votes.veto > 0
+        {
+            self.voter_reward = amount;
+        }
+        amount
+    }
+
+    /// Returns true once ballots are locked in the final window before `expires`
+    pub fn votes_locked(&self, block: &BlockInfo) -> bool {
+        match (self.params.vote_lock_period, self.expires) {
+            (Some(lock_period), Expiration::AtTime(time)) => {
+                block.time.seconds() + lock_period >= time.seconds()
+            }
+            _ => false,
+        }
+    }
+
     /// Returns true if this proposal is sure to pass (even before expiration, if no future
     /// sequence of possible votes could cause it to fail).
     pub fn is_passed(&self, _block: &BlockInfo) -> bool {
+        // multiple choice proposals need a single leading choice
+        if !self.choices.is_empty() && self.winning_choice().is_none() {
+            return false;
+        }
         match self.threshold {
             Threshold::AbsoluteCount {
                 weight: weight_needed,
@@ -120,7 +449,7 @@ impl Proposal {
                 if self.votes.total() < votes_needed(self.total_weight, quorum)
                     || self.votes.total() == self.votes.abstain
                     || self.votes.veto
-                        > (Decimal::percent(33) * Uint128::from(self.votes.total())).u128()
+                        > (self.veto_threshold * Uint128::from(self.votes.total())).u128()
                 {
                     false
                 } else {
@@ -133,6 +462,12 @@ impl Proposal {
     }
 
     pub fn is_rejected(&self, block: &BlockInfo) -> bool {
+        if !self.choices.is_empty()
+            && self.expires.is_expired(block)
+            && self.winning_choice().is_none()
+        {
+            return true;
+        }
         match self.threshold {
             Threshold::AbsoluteCount {
                 weight: weight_needed,
@@ -155,7 +490,7 @@ impl Proposal {
                 if self.votes.total() < votes_needed(self.total_weight, quorum)
                     || self.votes.total() == self.votes.abstain
                     || self.votes.veto
-                        > (Decimal::percent(33) * Uint128::from(self.votes.total())).u128()
+                        > (self.veto_threshold * Uint128::from(self.votes.total())).u128()
                     || self.votes.yes <= votes_needed(opinions, threshold)
                 {
                     true
@@ -195,7 +530,7 @@ impl Proposal {
             } => {
                 self.votes.total() > votes_needed(self.total_weight, quorum)
                     && self.votes.veto
-                        > (Decimal::percent(33).mul(Uint128::from(self.votes.total()))).u128()
+                        > (self.veto_threshold.mul(Uint128::from(self.votes.total()))).u128()
             }
         }
     }
@@ -243,6 +578,20 @@ impl Votes {
             Vote::Veto => self.veto -= weight,
         }
     }
+
+    pub fn add_weights(&mut self, weights: &VoteWeight) {
+        self.yes += weights.yes;
+        self.no += weights.no;
+        self.abstain += weights.abstain;
+        self.veto += weights.veto;
+    }
+
+    pub fn subtract_weights(&mut self, weights: &VoteWeight) {
+        self.yes -= weights.yes;
+        self.no -= weights.no;
+        self.abstain -= weights.abstain;
+        self.veto -= weights.veto;
+    }
 }
 
 // this is a helper function so Decimal works with u64 rather than Uint128
@@ -259,9 +608,41 @@ pub fn votes_needed(weight: u128, percentage: Decimal) -> u128 {
 pub struct Ballot {
     pub weight: u128,
     pub vote: Vote,
+    // part of `weight` that was handed over by delegators of the voter
+    #[serde(default)]
+    pub delegated_weight: u128,
+    // picked choice of a multiple choice proposal, for yes votes
+    #[serde(default)]
+    pub choice: Option<u32>,
+    // share of `weight` for each option of a split vote, `vote` is then the largest one
+    #[serde(default)]
+    pub options: Vec<(Vote, Decimal)>,
+}
+
+impl Ballot {
+    /// weight counted for each option, rounding leftovers of a split go to `vote`
+    pub fn vote_weight(&self) -> VoteWeight {
+        let mut weights = VoteWeight::default();
+        let mut counted = 0;
+        for (vote, share) in self.options.iter() {
+            let weight = (*share * Uint128::from(self.weight)).u128();
+            weights.add_vote(*vote, weight);
+            counted += weight;
+        }
+        weights.add_vote(self.vote, self.weight - counted);
+        weights
+    }
 }
 
+// weight of a delegator that was counted inside its delegate's ballot,
+// stored under the proposal and the delegator so a direct vote can take it back
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct DelegatedVote {
+    pub delegate: Addr,
+    pub weight: u128,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct VoteWeight {
     pub yes: u128,
     pub no: u128,
@@ -269,17 +650,131 @@ pub struct VoteWeight {
     pub veto: u128,
 }
 
+impl VoteWeight {
+    pub fn add_vote(&mut self, vote: Vote, weight: u128) {
+        match vote {
+            Vote::Yes => self.yes += weight,
+            Vote::Abstain => self.abstain += weight,
+            Vote::No => self.no += weight,
+            Vote::Veto => self.veto += weight,
+        }
+    }
+
+    /// non zero weights by option
+    pub fn options(&self) -> Vec<(Vote, u128)> {
+        vec![
+            (Vote::Yes, self.yes),
+            (Vote::No, self.no),
+            (Vote::Abstain, self.abstain),
+            (Vote::Veto, self.veto),
+        ]
+        .into_iter()
+        .filter(|(_, weight)| *weight > 0)
+        .collect()
+    }
+}
+
+pub struct ProposalIndexes<'a> {
+    // app_id
+    pub app: MultiIndex<'a, u64, Proposal, u64>,
+    // (app_id, stored status)
+    pub status: MultiIndex<'a, (u64, u8), Proposal, u64>,
+    // (app_id, proposer)
+    pub app_proposer: MultiIndex<'a, (u64, String), Proposal, u64>,
+    // proposer, across all apps
+    pub proposer: MultiIndex<'a, String, Proposal, u64>,
+}
+
+impl<'a> IndexList<Proposal> for ProposalIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Proposal>> + '_> {
+        let v: Vec<&dyn Index<Proposal>> =
+            vec![&self.app, &self.status, &self.app_proposer, &self.proposer];
+        Box::new(v.into_iter())
+    }
+}
+
+pub fn proposals<'a>() -> IndexedMap<'a, u64, Proposal, ProposalIndexes<'a>> {
+    let indexes = ProposalIndexes {
+        app: MultiIndex::new(|p| p.app_mapping_id, "proposals", "proposals__app"),
+        status: MultiIndex::new(
+            |p| (p.app_mapping_id, p.status as u8),
+            "proposals",
+            "proposals__status",
+        ),
+        app_proposer: MultiIndex::new(
+            |p| (p.app_mapping_id, p.proposer.clone()),
+            "proposals",
+            "proposals__app_proposer",
+        ),
+        proposer: MultiIndex::new(|p| p.proposer.clone(), "proposals", "proposals__proposer"),
+    };
+    IndexedMap::new("proposals", indexes)
+}
+
 // unique items
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const PROPOSAL_COUNT: Item<u64> = Item::new("proposal_count");
 
 // multiple-item map
 pub const BALLOTS: Map<(u64, &Addr), Ballot> = Map::new("ballots");
+// per-app proposal lists replaced by the indexes of `proposals()`, cleared by `migrate`
 pub const PROPOSALSBYAPP: Map<u64, Vec<u64>> = Map::new("proposals_by_app");
-pub const PROPOSALS: Map<u64, Proposal> = Map::new("proposals");
-pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter_deposit");
 pub const APPPROPOSALS: Map<u64, Vec<AppProposalConfig>> = Map::new("app_proposals");
+pub const VOTERDEPOSIT: Map<(u64, &Addr), Vec<Coin>> = Map::new("voter_deposit");
 pub const APPGOVCONFIG: Map<u64, AppGovConfig> = Map::new("app_gov_config");
+pub const APPGOVPARAMS: Map<u64, AppGovParams> = Map::new("app_gov_params");
+// (app_id, delegator) -> delegate
+pub const DELEGATIONS: Map<(u64, &Addr), Addr> = Map::new("delegations");
+// (app_id, delegate, delegator), reverse index of DELEGATIONS
+pub const DELEGATORS: Map<(u64, &Addr, &Addr), Empty> = Map::new("delegators");
+pub const DELEGATEDVOTES: Map<(u64, &Addr), DelegatedVote> = Map::new("delegated_votes");
+// (app_id, contract), contracts an app may execute and migrate through proposals
+pub const ALLOWEDCONTRACTS: Map<(u64, &Addr), Empty> = Map::new("allowed_contracts");
+// (voter, app_id, proposal_id), reverse index of BALLOTS
+pub const VOTERBALLOTS: Map<(&Addr, u64, u64), Empty> = Map::new("voter_ballots");
+// proposal_id => last depositor refunded by `RefundAll`
+pub const REFUNDCURSOR: Map<u64, Addr> = Map::new("refund_cursor");
+// denom => deposits held for the proposals of every app, no proposal may spend them
+pub const ESCROWEDDEPOSITS: Map<&str, Uint128> = Map::new("escrowed_deposits");
+// (proposal_id, voter) => share of the `voter_reward` paid to the voter
+pub const SLASHCLAIMS: Map<(u64, &Addr), Uint128> = Map::new("slash_claims");
+
+/// Adds tokens taken as a deposit to `ESCROWEDDEPOSITS`
+pub fn escrow_deposit(store: &mut dyn Storage, denom: &str, amount: u128) -> StdResult<()> {
+    ESCROWEDDEPOSITS.update(store, denom, |held| -> StdResult<_> {
+        Ok(held.unwrap_or_default() + Uint128::from(amount))
+    })?;
+    Ok(())
+}
+
+/// Takes deposits paid out, slashed or burnt off `ESCROWEDDEPOSITS`
+pub fn release_deposit(store: &mut dyn Storage, denom: &str, amount: u128) -> StdResult<()> {
+    ESCROWEDDEPOSITS.update(store, denom, |held| -> StdResult<_> {
+        Ok(held
+            .unwrap_or_default()
+            .saturating_sub(Uint128::from(amount)))
+    })?;
+    Ok(())
+}
+
+// (app_id, denom) => funds sent to the app's treasury and not spent by its proposals
+pub const APPTREASURY: Map<(u64, &str), Uint128> = Map::new("app_treasury");
+
+/// Adds funds sent to an app's treasury to `APPTREASURY`
+pub fn fund_treasury(store: &mut dyn Storage, app_id: u64, coin: &Coin) -> StdResult<()> {
+    APPTREASURY.update(store, (app_id, &coin.denom), |held| -> StdResult<_> {
+        Ok(held.unwrap_or_default() + coin.amount)
+    })?;
+    Ok(())
+}
+
+/// Takes funds spent by an executed proposal off `APPTREASURY`
+pub fn spend_treasury(store: &mut dyn Storage, app_id: u64, coin: &Coin) -> StdResult<()> {
+    APPTREASURY.update(store, (app_id, &coin.denom), |held| -> StdResult<_> {
+        Ok(held.unwrap_or_default().saturating_sub(coin.amount))
+    })?;
+    Ok(())
+}
 
 pub fn next_id(store: &mut dyn Storage) -> StdResult<u64> {
     let id: u64 = PROPOSAL_COUNT.may_load(store)?.unwrap_or_default() + 1;
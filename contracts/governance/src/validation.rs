@@ -4,23 +4,180 @@ use comdex_bindings::{
     TotalSupplyResponse,
 };
 
-use crate::msg::ExtendedPair;
+use crate::msg::{ExtendedPair, ProposalAction, ProposalMsg};
+use crate::state::{
+    AppGovParams, Config, SlashDestination, ALLOWEDCONTRACTS, APPTREASURY, ESCROWEDDEPOSITS,
+};
 
 #[cfg(not(feature = "library"))]
-use cosmwasm_std::{Coin, Decimal, Deps, QueryRequest, StdResult};
+use cosmwasm_std::{Addr, Api, Coin, Decimal, Deps, QueryRequest, StdResult, Uint128};
+use cw3::Vote;
+use cw_utils::Threshold;
+
+/// Only Quorum Threshold allowed for voting
+pub fn validate_quorum_threshold(
+    threshold: &Threshold,
+    veto_threshold: &Decimal,
+) -> Result<(), ContractError> {
+    match threshold {
+        Threshold::AbsoluteCount { .. } => Err(ContractError::AbsoluteCountNotAccepted {}),
+        Threshold::AbsolutePercentage { .. } => {
+            Err(ContractError::AbsolutePercentageNotAccepted {})
+        }
+        Threshold::ThresholdQuorum { threshold, quorum } => {
+            validate_threshold(threshold, quorum, veto_threshold)
+        }
+    }
+}
 
-pub fn validate_threshold(threshold: &Decimal, quorum: &Decimal) -> Result<(), ContractError> {
+pub fn validate_threshold(
+    threshold: &Decimal,
+    quorum: &Decimal,
+    veto_threshold: &Decimal,
+) -> Result<(), ContractError> {
     if *threshold > Decimal::percent(100) || *threshold < Decimal::percent(50) {
         Err(ContractError::InvalidThreshold {})
     } else if quorum.is_zero() {
         Err(ContractError::ZeroQuorumThreshold {})
     } else if *quorum > Decimal::one() {
         Err(ContractError::UnreachableQuorumThreshold {})
+    } else {
+        validate_veto_threshold(veto_threshold)
+    }
+}
+
+pub fn validate_veto_threshold(veto_threshold: &Decimal) -> Result<(), ContractError> {
+    if veto_threshold.is_zero() || *veto_threshold > Decimal::one() {
+        Err(ContractError::InvalidVetoThreshold {})
     } else {
         Ok(())
     }
 }
 
+/// validate checks for the params of an app, its threshold, quorum and veto overrides
+/// are checked as applied to the global ones of `cfg`
+pub fn validate_app_gov_params(params: &AppGovParams, cfg: &Config) -> Result<(), ContractError> {
+    validate_quorum_threshold(
+        &params.apply_threshold(&cfg.threshold),
+        params
+            .veto_threshold
+            .as_ref()
+            .unwrap_or(&cfg.veto_threshold),
+    )?;
+    if params.max_voting_period == Some(0) {
+        return Err(ContractError::ZeroVotingPeriod {});
+    }
+    if params.max_deposit_period == Some(0) {
+        return Err(ContractError::ZeroDepositPeriod {});
+    }
+    if let Some(min_initial_deposit) = params.min_initial_deposit {
+        if min_initial_deposit.is_zero() || min_initial_deposit > Decimal::one() {
+            return Err(ContractError::InvalidInitialDeposit {});
+        }
+    }
+    if let (Some(lock_period), Some(voting_period)) =
+        (params.vote_lock_period, params.max_voting_period)
+    {
+        if lock_period >= voting_period {
+            return Err(ContractError::InvalidVoteLockPeriod {});
+        }
+    }
+    if params.execution_grace_period == Some(0) {
+        return Err(ContractError::ZeroGracePeriod {});
+    }
+    if let Some(slash_fraction) = params.slash_fraction {
+        if slash_fraction.is_zero() || slash_fraction > Decimal::one() {
+            return Err(ContractError::InvalidSlashFraction {});
+        }
+    }
+    Ok(())
+}
+
+/// validate checks for actions on the contract's own config `cfg`
+pub fn validate_proposal_action(
+    api: &dyn Api,
+    action: &ProposalAction,
+    cfg: &Config,
+) -> Result<(), ContractError> {
+    match action {
+        ProposalAction::UpdateThreshold {
+            threshold,
+            veto_threshold,
+        } => validate_quorum_threshold(
+            threshold,
+            veto_threshold.as_ref().unwrap_or(&cfg.veto_threshold),
+        ),
+        ProposalAction::UpdateLockingContract { address } => {
+            api.addr_validate(address.as_str())?;
+            Ok(())
+        }
+        ProposalAction::UpdateAppGovParams { params } => {
+            if let SlashDestination::CommunityPool { address } = &params.slash_destination {
+                api.addr_validate(address.as_str())?;
+            }
+            validate_app_gov_params(params, cfg)
+        }
+        ProposalAction::UpdateGuardian { guardian } => {
+            if let Some(guardian) = guardian {
+                api.addr_validate(guardian.as_str())?;
+            }
+            Ok(())
+        }
+        ProposalAction::UpdateAllowedContracts { add, remove } => {
+            for contract in add.iter().chain(remove.iter()) {
+                api.addr_validate(contract.as_str())?;
+            }
+            Ok(())
+        }
+        ProposalAction::UpdateAdminApp { .. } => Ok(()),
+    }
+}
+
+/// options of a split vote must be distinct, non zero and add up to 100%
+pub fn validate_vote_options(options: &[(Vote, Decimal)]) -> Result<(), ContractError> {
+    let mut total = Decimal::zero();
+    for (i, (vote, share)) in options.iter().enumerate() {
+        if share.is_zero() || options[..i].iter().any(|(prev, _)| prev == vote) {
+            return Err(ContractError::InvalidVoteOptions {});
+        }
+        total += *share;
+    }
+    if total != Decimal::one() {
+        return Err(ContractError::InvalidVoteOptions {});
+    }
+    Ok(())
+}
+
+/// validate checks for bank and wasm messages of a proposal,
+/// the funds they spend are checked by `validate_treasury_spends`
+pub fn validate_proposal_msg(
+    deps: Deps<ComdexQuery>,
+    msg: &ProposalMsg,
+    app_id: u64,
+) -> Result<(), ContractError> {
+    let contract_addr = match msg {
+        ProposalMsg::BankSend { to_address, amount } => {
+            deps.api.addr_validate(to_address)?;
+            if amount.is_empty() {
+                return Err(ContractError::InvalidTreasurySpend {});
+            }
+            None
+        }
+        ProposalMsg::WasmExecute { contract_addr, .. }
+        | ProposalMsg::WasmMigrate { contract_addr, .. } => Some(contract_addr),
+    };
+    if msg.funds().iter().any(|coin| coin.amount.is_zero()) {
+        return Err(ContractError::InvalidTreasurySpend {});
+    }
+    if let Some(contract_addr) = contract_addr {
+        let contract_addr = deps.api.addr_validate(contract_addr)?;
+        if !ALLOWEDCONTRACTS.has(deps.storage, (app_id, &contract_addr)) {
+            return Err(ContractError::ContractNotAllowed {});
+        }
+    }
+    Ok(())
+}
+
 /// validate checks to update vault stability fee
 pub fn update_pairvault_stability(
     deps: Deps<ComdexQuery>,
@@ -351,6 +508,105 @@ pub fn set_esm_params(
     }
 }
 
+/// checks to add auction params for an app
+pub fn add_auction_params(
+    deps: Deps<ComdexQuery>,
+    app_mapping_id_param: u64,
+    app_id: u64,
+) -> Result<(), ContractError> {
+    if app_mapping_id_param != app_id {
+        return Err(ContractError::DifferentAppID {});
+    }
+    let query = ComdexQuery::AddAuctionParamsQuery {
+        app_id: app_mapping_id_param,
+    };
+    let query_result = deps
+        .querier
+        .query::<MessageValidateResponse>(&QueryRequest::Custom(query))?;
+
+    if query_result.found {
+        Ok(())
+    } else {
+        let err = query_result.err;
+        Err(ContractError::ProposalError { err })
+    }
+}
+
+/// checks to burn gov tokens of an app, a burn is legal if it is for the app of the
+/// proposal, burns from this contract and only a non zero amount of the app's gov token,
+/// at most the `burnable` part of the balance that does not back any deposit
+pub fn burn_gov_tokens_for_app(
+    app_mapping_id_param: u64,
+    app_id: u64,
+    amount: &Coin,
+    from: &str,
+    contract_addr: &Addr,
+    gov_token_denom: &str,
+    burnable: Uint128,
+) -> Result<(), ContractError> {
+    if app_mapping_id_param != app_id {
+        return Err(ContractError::DifferentAppID {});
+    }
+    if from != contract_addr.as_str() || amount.denom != gov_token_denom || amount.amount.is_zero()
+    {
+        return Err(ContractError::InvalidBurn {});
+    }
+    if amount.amount > burnable {
+        return Err(ContractError::BurnExceedsTreasury {});
+    }
+    Ok(())
+}
+
+/// gov tokens held by the contract beyond the deposits of all proposals
+pub fn burnable_balance(
+    deps: Deps<ComdexQuery>,
+    contract_addr: &Addr,
+    denom: &str,
+) -> StdResult<Uint128> {
+    let balance = deps.querier.query_balance(contract_addr, denom)?.amount;
+    let escrowed = ESCROWEDDEPOSITS
+        .may_load(deps.storage, denom)?
+        .unwrap_or_default();
+    Ok(balance.saturating_sub(escrowed))
+}
+
+/// funds of the app's treasury its proposals can spend, at most what the contract
+/// holds beyond the deposits of all proposals
+pub fn treasury_balance(
+    deps: Deps<ComdexQuery>,
+    contract_addr: &Addr,
+    app_id: u64,
+    denom: &str,
+) -> StdResult<Uint128> {
+    let funded = APPTREASURY
+        .may_load(deps.storage, (app_id, denom))?
+        .unwrap_or_default();
+    Ok(funded.min(burnable_balance(deps, contract_addr, denom)?))
+}
+
+/// validate checks that the funds of the msgs, added up per denom, stay within the
+/// treasury of the app, returns the totals to take off the treasury on execution
+pub fn validate_treasury_spends(
+    deps: Deps<ComdexQuery>,
+    contract_addr: &Addr,
+    app_id: u64,
+    msgs: &[ProposalMsg],
+) -> Result<Vec<Coin>, ContractError> {
+    let mut spends: Vec<Coin> = vec![];
+    for coin in msgs.iter().flat_map(ProposalMsg::funds) {
+        match spends.iter_mut().find(|spend| spend.denom == coin.denom) {
+            Some(spend) => spend.amount += coin.amount,
+            None => spends.push(coin.clone()),
+        }
+    }
+    for spend in spends.iter() {
+        if spend.amount > treasury_balance(deps, contract_addr, app_id, &spend.denom)? {
+            return Err(ContractError::SpendExceedsTreasury {});
+        }
+    }
+    Ok(spends)
+}
+
 /// query token balance of a user for a denom at a specific height
 pub fn query_owner_token_at_height(
     deps: Deps<ComdexQuery>,
@@ -416,44 +672,329 @@ pub fn get_token_supply(
 #[cfg(test)]
 mod validation_tests {
     use super::*;
+    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{to_binary, ContractResult, OwnedDeps, SystemResult};
+    use std::marker::PhantomData;
 
     #[test]
     fn test_threshold() {
         let threshold = Decimal::one();
         let quorum = Decimal::from_ratio(3u8, 4u8);
+        let veto_threshold = Decimal::percent(33);
 
         // simple validation with correct values
-        validate_threshold(&threshold, &quorum).unwrap();
+        validate_threshold(&threshold, &quorum, &veto_threshold).unwrap();
 
         // FAILURES
         // threshold greater than 100%
-        let result =
-            validate_threshold(&Decimal::from_atomics(11u128, 1).unwrap(), &quorum).unwrap_err();
+        let result = validate_threshold(
+            &Decimal::from_atomics(11u128, 1).unwrap(),
+            &quorum,
+            &veto_threshold,
+        )
+        .unwrap_err();
         match result {
             ContractError::InvalidThreshold {} => {}
             e => panic!("{:?}", e),
         };
 
         // quorum greater than 100%
-        let result =
-            validate_threshold(&Decimal::one(), &Decimal::from_ratio(2u8, 1u8)).unwrap_err();
+        let result = validate_threshold(
+            &Decimal::one(),
+            &Decimal::from_ratio(2u8, 1u8),
+            &veto_threshold,
+        )
+        .unwrap_err();
         match result {
             ContractError::UnreachableQuorumThreshold {} => {}
             e => panic!("{:?}", e),
         };
 
         // threshold is zero
-        let result = validate_threshold(&Decimal::zero(), &Decimal::one()).unwrap_err();
+        let result =
+            validate_threshold(&Decimal::zero(), &Decimal::one(), &veto_threshold).unwrap_err();
         match result {
             ContractError::InvalidThreshold {} => {}
             e => panic!("{:?}", e),
         };
 
         // quorum is zero
-        let result = validate_threshold(&Decimal::one(), &Decimal::zero()).unwrap_err();
+        let result =
+            validate_threshold(&Decimal::one(), &Decimal::zero(), &veto_threshold).unwrap_err();
         match result {
             ContractError::ZeroQuorumThreshold {} => {}
             e => panic!("{:?}", e),
         };
+
+        // veto threshold is zero or greater than 100%
+        let result = validate_threshold(&threshold, &quorum, &Decimal::zero()).unwrap_err();
+        assert_eq!(result, ContractError::InvalidVetoThreshold {});
+        let result = validate_threshold(&threshold, &quorum, &Decimal::percent(101)).unwrap_err();
+        assert_eq!(result, ContractError::InvalidVetoThreshold {});
+    }
+
+    #[test]
+    fn test_burn_gov_tokens_for_app() {
+        let contract_addr = Addr::unchecked("governance");
+        let amount = Coin::new(100, "ugov");
+        let burnable = Uint128::from(100_u64);
+
+        burn_gov_tokens_for_app(
+            1,
+            1,
+            &amount,
+            "governance",
+            &contract_addr,
+            "ugov",
+            burnable,
+        )
+        .unwrap();
+
+        // FAILURES
+        // burn for another app
+        let result = burn_gov_tokens_for_app(
+            1,
+            2,
+            &amount,
+            "governance",
+            &contract_addr,
+            "ugov",
+            burnable,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::DifferentAppID {});
+
+        // burn from another account
+        let result =
+            burn_gov_tokens_for_app(1, 1, &amount, "treasury", &contract_addr, "ugov", burnable)
+                .unwrap_err();
+        assert_eq!(result, ContractError::InvalidBurn {});
+
+        // burn of another denom
+        let result = burn_gov_tokens_for_app(
+            1,
+            1,
+            &Coin::new(100, "ucmdx"),
+            "governance",
+            &contract_addr,
+            "ugov",
+            burnable,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidBurn {});
+
+        // burn of nothing
+        let result = burn_gov_tokens_for_app(
+            1,
+            1,
+            &Coin::new(0, "ugov"),
+            "governance",
+            &contract_addr,
+            "ugov",
+            burnable,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidBurn {});
+
+        // burn of deposits held for proposals
+        let result = burn_gov_tokens_for_app(
+            1,
+            1,
+            &amount,
+            "governance",
+            &contract_addr,
+            "ugov",
+            Uint128::from(99_u64),
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::BurnExceedsTreasury {});
+    }
+
+    #[test]
+    fn test_add_auction_params() {
+        let querier = MockQuerier::<ComdexQuery>::new(&[]).with_custom_handler(|query| {
+            let found = matches!(query, ComdexQuery::AddAuctionParamsQuery { app_id: 1 });
+            SystemResult::Ok(ContractResult::Ok(
+                to_binary(&MessageValidateResponse {
+                    found,
+                    err: "auction params not set".to_string(),
+                })
+                .unwrap(),
+            ))
+        });
+        let deps = OwnedDeps {
+            storage: MockStorage::default(),
+            api: MockApi::default(),
+            querier,
+            custom_query_type: PhantomData::<ComdexQuery>,
+        };
+
+        add_auction_params(deps.as_ref(), 1, 1).unwrap();
+
+        // FAILURES
+        // params of another app
+        let result = add_auction_params(deps.as_ref(), 1, 2).unwrap_err();
+        assert_eq!(result, ContractError::DifferentAppID {});
+
+        // rejected by the chain
+        let result = add_auction_params(deps.as_ref(), 2, 2).unwrap_err();
+        assert_eq!(
+            result,
+            ContractError::ProposalError {
+                err: "auction params not set".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_vote_options() {
+        validate_vote_options(&[
+            (Vote::Yes, Decimal::percent(70)),
+            (Vote::Abstain, Decimal::percent(30)),
+        ])
+        .unwrap();
+
+        // FAILURES
+        // not adding up to 100%
+        let result = validate_vote_options(&[
+            (Vote::Yes, Decimal::percent(70)),
+            (Vote::No, Decimal::percent(20)),
+        ])
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidVoteOptions {});
+
+        // the same option twice
+        let result = validate_vote_options(&[
+            (Vote::Yes, Decimal::percent(50)),
+            (Vote::Yes, Decimal::percent(50)),
+        ])
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidVoteOptions {});
+
+        // zero share
+        let result =
+            validate_vote_options(&[(Vote::Yes, Decimal::one()), (Vote::Veto, Decimal::zero())])
+                .unwrap_err();
+        assert_eq!(result, ContractError::InvalidVoteOptions {});
+
+        // no option
+        let result = validate_vote_options(&[]).unwrap_err();
+        assert_eq!(result, ContractError::InvalidVoteOptions {});
+    }
+
+    #[test]
+    fn test_app_gov_params() {
+        let cfg = Config {
+            threshold: Threshold::ThresholdQuorum {
+                threshold: Decimal::percent(50),
+                quorum: Decimal::percent(33),
+            },
+            veto_threshold: Decimal::percent(33),
+            locking_contract: Addr::unchecked("locking-contract"),
+            guardian: None,
+            admin_app_id: None,
+        };
+        // no overrides
+        validate_app_gov_params(&AppGovParams::default(), &cfg).unwrap();
+
+        let params = AppGovParams {
+            threshold: Some(Decimal::percent(60)),
+            quorum: Some(Decimal::percent(20)),
+            veto_threshold: Some(Decimal::percent(50)),
+            max_voting_period: Some(3600),
+            ..AppGovParams::default()
+        };
+        validate_app_gov_params(&params, &cfg).unwrap();
+
+        // FAILURES
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                threshold: Some(Decimal::percent(40)),
+                ..params.clone()
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidThreshold {});
+
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                quorum: Some(Decimal::zero()),
+                ..params.clone()
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::ZeroQuorumThreshold {});
+
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                quorum: Some(Decimal::percent(101)),
+                ..params.clone()
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::UnreachableQuorumThreshold {});
+
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                veto_threshold: Some(Decimal::zero()),
+                ..params.clone()
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidVetoThreshold {});
+
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                min_initial_deposit: Some(Decimal::percent(101)),
+                ..params.clone()
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidInitialDeposit {});
+
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                max_deposit_period: Some(0),
+                ..params.clone()
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::ZeroDepositPeriod {});
+
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                slash_fraction: Some(Decimal::zero()),
+                ..params.clone()
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidSlashFraction {});
+
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                vote_lock_period: Some(3600),
+                ..params.clone()
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::InvalidVoteLockPeriod {});
+
+        let result = validate_app_gov_params(
+            &AppGovParams {
+                max_voting_period: Some(0),
+                ..params
+            },
+            &cfg,
+        )
+        .unwrap_err();
+        assert_eq!(result, ContractError::ZeroVotingPeriod {});
     }
 }
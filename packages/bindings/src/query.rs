@@ -78,6 +78,9 @@ pub enum ComdexQuery {
     AddESMTriggerParamsForAppQuery {
         app_id: u64,
     },
+    AddAuctionParamsQuery {
+        app_id: u64,
+    },
 }
 
 impl CustomQuery for ComdexQuery {}
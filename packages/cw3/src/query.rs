@@ -78,6 +78,12 @@ pub enum Status {
     Passed = 4,
     /// voting is over it passed, and the proposal was executed
     Executed = 5,
+    /// proposal was withdrawn by its proposer and the deposits were refunded
+    Cancelled = 6,
+    /// proposal passed, but the guardian vetoed it before execution
+    Vetoed = 7,
+    /// min deposit was not reached within the deposit period
+    DepositFailed = 8,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -98,6 +104,9 @@ pub struct VoteInfo {
     pub voter: String,
     pub vote: Vote,
     pub weight: u128,
+    /// Weight counted for each option of a split vote, empty for single option votes
+    #[serde(default)]
+    pub options: Vec<(Vote, u128)>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]